@@ -1,4 +1,5 @@
 use crate::game::board::BOARD_HEIGHT;
+use serde::Deserialize;
 use std::ops::Range;
 use std::time::Duration;
 
@@ -6,7 +7,8 @@ const CURTAIN_LINE_DELAY: Duration = Duration::from_millis(30);
 const CURTAIN_CLOSED_FOR: Duration = Duration::from_millis(2000);
 const CURTAIN_OPEN_FOR: Duration = Duration::from_millis(2000);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GameOverAnimationType {
     CurtainUp,
     CurtainDown,
@@ -0,0 +1,97 @@
+use crate::config::config_path;
+use sdl2::image::SaveSurface;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::WindowCanvas;
+use sdl2::surface::Surface;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const PIXEL_FORMAT: PixelFormatEnum = PixelFormatEnum::RGB24;
+
+/// Saves the frame being drawn as a PNG, this has to happen before the canvas is presented
+fn save_png(canvas: &WindowCanvas, path: &Path) -> Result<(), String> {
+    let (width, height) = canvas.output_size()?;
+    let mut pixels = canvas.read_pixels(None, PIXEL_FORMAT)?;
+    let pitch = PIXEL_FORMAT.byte_size_of_pixels(width as usize) as u32;
+    let surface = Surface::from_data(&mut pixels, width, height, pitch, PIXEL_FORMAT)?;
+    surface.save(path)
+}
+
+fn timestamp() -> Result<u128, String> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_millis())
+        .map_err(|e| e.to_string())
+}
+
+/// Saves the frame being drawn to a timestamped PNG in the config directory
+pub fn save_screenshot(canvas: &WindowCanvas) -> Result<(), String> {
+    let path = config_path(&format!("screenshot-{}", timestamp()?))?.with_extension("png");
+    save_png(canvas, &path)
+}
+
+/// Dumps every nth frame of a match to a numbered PNG sequence, in a folder per match
+pub struct Capture {
+    folder: PathBuf,
+    every_nth_frame: u32,
+    frame: u32,
+}
+
+impl Capture {
+    pub fn new(every_nth_frame: u32) -> Result<Self, String> {
+        let folder = config_path(&format!("capture-{}", timestamp()?))?.with_extension("");
+        std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+        Ok(Self::in_folder(folder, every_nth_frame))
+    }
+
+    fn in_folder(folder: PathBuf, every_nth_frame: u32) -> Self {
+        Self {
+            folder,
+            every_nth_frame: every_nth_frame.max(1),
+            frame: 0,
+        }
+    }
+
+    /// Where to save the next frame, None when it is skipped
+    fn next_path(&mut self) -> Option<PathBuf> {
+        let frame = self.frame;
+        self.frame += 1;
+        if frame.is_multiple_of(self.every_nth_frame) {
+            let index = frame / self.every_nth_frame;
+            Some(self.folder.join(format!("{:06}.png", index)))
+        } else {
+            None
+        }
+    }
+
+    /// Registers a frame being drawn, saving it if it is one of the nth frames
+    pub fn frame(&mut self, canvas: &WindowCanvas) -> Result<(), String> {
+        match self.next_path() {
+            Some(path) => save_png(canvas, &path),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_every_nth_frame_in_sequence() {
+        let mut capture = Capture::in_folder(PathBuf::from("capture"), 3);
+        let paths = (0..7).map(|_| capture.next_path()).collect::<Vec<Option<PathBuf>>>();
+        assert_eq!(
+            paths,
+            vec![
+                Some(PathBuf::from("capture/000000.png")),
+                None,
+                None,
+                Some(PathBuf::from("capture/000001.png")),
+                None,
+                None,
+                Some(PathBuf::from("capture/000002.png")),
+            ]
+        );
+    }
+}
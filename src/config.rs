@@ -1,12 +1,14 @@
 use num_format::{Locale, ToFormattedString};
-use crate::game::random::RandomMode;
+use crate::game::random::{GarbageMode, RandomMode};
+use crate::game::rotation::RotationSystem;
+use crate::game::timing::TimingPreset;
 use crate::game_input::GameInputKey;
 use crate::menu_input::MenuInputKey;
 use sdl2::keyboard::Keycode;
 use sdl2::mixer::MAX_VOLUME;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use confy::ConfyError;
 use sdl2::sys;
 use strum::IntoEnumIterator;
@@ -24,6 +26,10 @@ pub struct Config {
     pub audio: AudioConfig,
     pub input: InputConfig,
     pub game: GameplayConfig,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    #[serde(default)]
+    pub capture: CaptureConfig,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -44,7 +50,11 @@ pub struct GameInputConfig {
     pub hard_drop: GameKey,
     pub rotate_clockwise: GameKey,
     pub rotate_anticlockwise: GameKey,
+    #[serde(default)]
+    pub rotate_180: Option<GameKey>,
     pub hold: GameKey,
+    #[serde(default)]
+    pub undo: Option<GameKey>,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -55,6 +65,21 @@ pub struct InputConfig {
     pub pause: GameKey,
     pub quit: GameKey,
     pub next_theme: GameKey,
+    #[serde(default)]
+    pub export_board: Option<GameKey>,
+    #[serde(default)]
+    pub import_board: Option<GameKey>,
+    #[serde(default)]
+    pub restart: Option<GameKey>,
+    /// Toggle between the window and desktop fullscreen
+    #[serde(default)]
+    pub fullscreen: Option<GameKey>,
+    /// Toggle the frame rate & timings overlay
+    #[serde(default)]
+    pub debug_overlay: Option<GameKey>,
+    /// Save the screen to a PNG
+    #[serde(default)]
+    pub screenshot: Option<GameKey>,
 }
 
 impl InputConfig {
@@ -93,6 +118,23 @@ impl InputConfig {
             (self.player1.hold.into(), GameInputKey::Hold { player: 1 }),
         ]);
 
+        if let Some(export_board) = self.export_board {
+            result.insert(export_board.into(), GameInputKey::ExportBoard);
+        }
+        if let Some(import_board) = self.import_board {
+            result.insert(import_board.into(), GameInputKey::ImportBoard);
+        }
+        if let Some(restart) = self.restart {
+            result.insert(restart.into(), GameInputKey::Restart);
+        }
+
+        if let Some(rotate_180) = self.player1.rotate_180 {
+            result.insert(rotate_180.into(), GameInputKey::Rotate180 { player: 1 });
+        }
+        if let Some(undo) = self.player1.undo {
+            result.insert(undo.into(), GameInputKey::Undo { player: 1 });
+        }
+
         match self.player2 {
             None => {}
             Some(p2) => {
@@ -109,6 +151,12 @@ impl InputConfig {
                     GameInputKey::RotateClockwise { player: 2 },
                 );
                 result.insert(p2.hold.into(), GameInputKey::Hold { player: 2 });
+                if let Some(rotate_180) = p2.rotate_180 {
+                    result.insert(rotate_180.into(), GameInputKey::Rotate180 { player: 2 });
+                }
+                if let Some(undo) = p2.undo {
+                    result.insert(undo.into(), GameInputKey::Undo { player: 2 });
+                }
             }
         }
 
@@ -130,6 +178,12 @@ impl AudioConfig {
     pub fn effects_volume(&self) -> i32 {
         (self.effects_volume * MAX_VOLUME as f64).round() as i32
     }
+
+    /// Set the mixer volumes, effects are set on the channels so they can change mid game
+    pub fn apply(&self) {
+        sdl2::mixer::Music::set_volume(self.music_volume());
+        sdl2::mixer::Channel::all().set_volume(self.effects_volume());
+    }
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -137,7 +191,10 @@ pub struct VideoConfig {
     pub mode: VideoMode,
     pub vsync: bool,
     pub disable_screensaver: bool,
-    pub integer_scale: bool
+    pub integer_scale: bool,
+    /// Caps the frame rate, the loop otherwise runs as fast as it can when vsync is off
+    #[serde(default)]
+    pub max_fps: Option<u32>,
 }
 
 impl VideoConfig {
@@ -151,10 +208,71 @@ impl VideoConfig {
     }
 }
 
+/// Replacement mino colours that do not rely on telling apart the theme colours
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MinoPalette {
+    /// Keep the theme colours
+    #[default]
+    Theme,
+    /// Okabe-Ito colours that stay distinct with the common colour vision deficiencies
+    ColorBlind,
+    /// Flat saturated colours with distinct brightness
+    HighContrast,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct AccessibilityConfig {
+    #[serde(default)]
+    pub palette: MinoPalette,
+    /// Mark every mino with a glyph of its shape
+    #[serde(default)]
+    pub glyphs: bool,
+    /// No screen shake, background particles or theme fades and fewer foreground particles
+    #[serde(default)]
+    pub reduced_motion: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct CaptureConfig {
+    /// Dump every nth frame of every match to a PNG sequence
+    #[serde(default)]
+    pub every_nth_frame: Option<u32>,
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct GameplayConfig {
     pub random_mode: RandomMode,
-    pub min_garbage_per_hole: u32,
+    #[serde(default)]
+    pub garbage_mode: GarbageMode,
+    /// Replaced by garbage_mode, only read from old configs
+    #[serde(default, skip_serializing)]
+    min_garbage_per_hole: Option<u32>,
+    #[serde(default)]
+    pub rotation_system: RotationSystem,
+    /// Apply held rotate & hold keys to spawning tetrominos (IRS & IHS)
+    /// and buffer inputs made during the spawn delay
+    #[serde(default = "default_buffer_inputs")]
+    pub buffer_inputs: bool,
+    #[serde(default)]
+    pub timings: TimingPreset,
+    /// Restarting a match deals the same tetrominos again
+    #[serde(default)]
+    pub restart_same_seed: bool,
+}
+
+/// On by default, including for configs saved before the option existed
+fn default_buffer_inputs() -> bool {
+    true
+}
+
+impl GameplayConfig {
+    /// Old configs only had the rate that the clean garbage hole moves at
+    fn migrate(mut self) -> Self {
+        if let Some(lines_per_hole) = self.min_garbage_per_hole.take() {
+            self.garbage_mode = GarbageMode::Clean { lines_per_hole };
+        }
+        self
+    }
 }
 
 impl Default for Config {
@@ -176,7 +294,8 @@ impl Default for Config {
 
                 // disable integer scaling to better fill small retro handheld screen
                 // otherwise keep it enabled as it does look better
-                integer_scale: !cfg!(feature = "retro_handheld")
+                integer_scale: !cfg!(feature = "retro_handheld"),
+                max_fps: None,
             },
             audio: AudioConfig {
                 music_volume: 1.0,
@@ -211,7 +330,9 @@ impl Default for Config {
                     hard_drop: GameKey::Up,
                     rotate_clockwise: GameKey::X,
                     rotate_anticlockwise: GameKey::Z,
+                    rotate_180: Some(GameKey::A),
                     hold: GameKey::LShift,
+                    undo: Some(GameKey::C),
                 },
                 player2: None,
                 #[cfg(feature = "retro_handheld")] pause: GameKey::Return,
@@ -219,11 +340,24 @@ impl Default for Config {
                 #[cfg(feature = "retro_handheld")] next_theme: GameKey::RShift,
                 #[cfg(not(feature = "retro_handheld"))] next_theme: GameKey::F2,
                 quit: GameKey::Escape,
+                export_board: Some(GameKey::F3),
+                import_board: Some(GameKey::F5),
+                restart: Some(GameKey::F4),
+                fullscreen: Some(GameKey::F11),
+                debug_overlay: Some(GameKey::F10),
+                screenshot: Some(GameKey::F12),
             },
             game: GameplayConfig {
                 random_mode: RandomMode::Bag,
-                min_garbage_per_hole: 10,
+                garbage_mode: GarbageMode::default(),
+                min_garbage_per_hole: None,
+                rotation_system: RotationSystem::Srs,
+                buffer_inputs: default_buffer_inputs(),
+                timings: TimingPreset::Guideline,
+                restart_same_seed: false,
             },
+            accessibility: AccessibilityConfig::default(),
+            capture: CaptureConfig::default(),
         }
     }
 }
@@ -249,18 +383,41 @@ impl Config {
         #[cfg(debug_assertions)]
         println!("loading config: {}", config_path.to_str().unwrap());
 
-        match confy::load_path(&config_path) {
-            Ok(config) => Ok(config),
+        Ok(Self::read(&config_path)?.unwrap_or_default())
+    }
+
+    /// Saves only the audio settings into the config file, leaving a bad config file alone
+    pub fn save_audio(audio: AudioConfig) -> Result<(), String> {
+        let config_path = config_path("config")?;
+        match Self::read(&config_path)? {
+            Some(config) => {
+                confy::store_path(config_path, Self { audio, ..config }).map_err(|e| e.to_string())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// None when the config file is bad
+    fn read(config_path: &Path) -> Result<Option<Self>, String> {
+        match confy::load_path::<Self>(config_path) {
+            Ok(config) => Ok(Some(Self {
+                game: config.game.migrate(),
+                ..config
+            })),
             Err(ConfyError::BadYamlData(error)) => {
-                println!("Bad config file at {}, {}, loading defaults", config_path.to_str().unwrap(), error);
-                Ok(Self::default())
+                println!(
+                    "Bad config file at {}, {}, using defaults without saving",
+                    config_path.to_str().unwrap(),
+                    error
+                );
+                Ok(None)
             }
             Err(error) => Err(format!("{}", error)),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MatchRules {
     /// Endless game with garbage
     Battle,
@@ -270,58 +427,115 @@ pub enum MatchRules {
     LineSprint { lines: u32 },
     /// Endless game
     Marathon,
+    /// Levels count to 999 with gravity reaching 20G, timed by section
+    Master,
+    /// First to clear some number of pre-filled garbage lines
+    Dig { lines: u32 },
+    /// Garbage rises on an accelerating timer, last as long as possible
+    Survival,
+    /// Endless practice that never ends, optionally without gravity
+    Zen { gravity: bool },
+    /// Solve an authored board setup, indexing the loaded puzzles
+    Puzzle { index: usize },
 }
 
 impl MatchRules {
     pub const DEFAULT_LINE_SPRINT: Self = Self::LineSprint { lines: 40 };
     pub const DEFAULT_SCORE_SPRINT: Self = Self::ScoreSprint { score: 10_000 };
+    pub const DEFAULT_DIG: Self = Self::Dig { lines: 10 };
 
-    pub const DEFAULT_MODES: [Self; 4] = [
+    pub const DEFAULT_MODES: [Self; 10] = [
         Self::Battle,
         Self::DEFAULT_LINE_SPRINT,
         Self::DEFAULT_SCORE_SPRINT,
-        Self::Marathon
+        Self::Marathon,
+        Self::Master,
+        Self::DEFAULT_DIG,
+        Self::Survival,
+        Self::Zen { gravity: true },
+        Self::Zen { gravity: false },
+        Self::Puzzle { index: 0 }
     ];
 
     pub fn garbage_enabled(&self) -> bool {
         self == &MatchRules::Battle
     }
 
+    /// Modes that show a results screen when the match is over
+    pub fn has_results(&self) -> bool {
+        matches!(
+            self,
+            MatchRules::Master
+                | MatchRules::Dig { .. }
+                | MatchRules::Survival
+                | MatchRules::Puzzle { .. }
+        )
+    }
+
     pub fn name(&self) -> String {
         match self {
             MatchRules::Battle => "battle".to_string(),
             MatchRules::ScoreSprint { score } => format!("{} point sprint", score.to_formatted_string(&Locale::en)),
             MatchRules::LineSprint { lines } => format!("{} line sprint", lines.to_formatted_string(&Locale::en)),
-            MatchRules::Marathon => "marathon".to_string()
+            MatchRules::Marathon => "marathon".to_string(),
+            MatchRules::Master => "master".to_string(),
+            MatchRules::Dig { lines } => format!("{} line dig", lines),
+            MatchRules::Survival => "survival".to_string(),
+            MatchRules::Zen { gravity: true } => "zen".to_string(),
+            MatchRules::Zen { gravity: false } => "zen no gravity".to_string(),
+            MatchRules::Puzzle { .. } => "puzzle".to_string()
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::IntoStaticStr, strum::EnumIter, strum::EnumString)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize,
+    strum::IntoStaticStr, strum::EnumIter, strum::EnumString,
+)]
 pub enum MatchThemes {
     /// Run themes in order, switching at the next level
     #[strum(serialize = "all")]
     All,
     #[strum(serialize = "gameboy")]
     GameBoy,
+    #[strum(serialize = "gameboy color")]
+    GameBoyColor,
     #[strum(serialize = "nes")]
     Nes,
     #[strum(serialize = "snes")]
     Snes,
     #[strum(serialize = "modern")]
     Modern,
+    /// Only the theme pack at this index, packs are named after their folder in the menu
+    #[strum(disabled)]
+    Pack(usize),
 }
 
 impl MatchThemes {
+    /// Names of the built in themes, packs are listed after these
     pub fn names() -> Vec<&'static str> {
         Self::iter().map(|e| e.into()).collect()
     }
     pub fn count() -> usize {
-        Self::iter().filter(|i| *i as usize > 0).count()
+        Self::iter().filter(|i| *i != Self::All).count()
+    }
+
+    /// Position in a menu listing the built in themes followed by the packs
+    pub fn menu_index(&self) -> usize {
+        match self {
+            Self::Pack(index) => Self::names().len() + index,
+            _ => Self::iter().position(|i| i == *self).unwrap(),
+        }
+    }
+
+    pub fn from_menu_index(index: usize) -> Self {
+        Self::iter()
+            .nth(index)
+            .unwrap_or_else(|| Self::Pack(index - Self::names().len()))
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GameConfig {
     pub players: u32,
     pub level: u32,
@@ -598,4 +812,70 @@ impl Into<Keycode> for GameKey {
     fn into(self) -> Keycode {
         Keycode::from_i32(self as i32).expect("Invalid keycode")
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The default config as saved before garbage modes & rotation systems
+    const LEGACY_CONFIG: &str = "
+video:
+  mode: !Window
+    width: 1280
+    height: 720
+  vsync: true
+  disable_screensaver: true
+  integer_scale: true
+audio:
+  music_volume: 1.0
+  effects_volume: 1.0
+input:
+  menu:
+    up: Up
+    down: Down
+    left: Left
+    right: Right
+    select: X
+    start: Return
+  player1:
+    move_left: Left
+    move_right: Right
+    soft_drop: Down
+    hard_drop: Up
+    rotate_clockwise: X
+    rotate_anticlockwise: Z
+    hold: LShift
+  player2: null
+  pause: F1
+  quit: Escape
+  next_theme: F2
+game:
+  random_mode: Bag
+  min_garbage_per_hole: 5
+";
+
+    #[test]
+    fn loads_legacy_config() {
+        let config = serde_yaml::from_str::<Config>(LEGACY_CONFIG).unwrap();
+        let game = config.game.migrate();
+        assert_eq!(game.garbage_mode, GarbageMode::Clean { lines_per_hole: 5 });
+        assert_eq!(game.rotation_system, RotationSystem::Srs);
+        assert!(game.buffer_inputs);
+        assert_eq!(config.input.pause, GameKey::F1);
+
+        let saved = serde_yaml::to_string(&Config { game, ..config }).unwrap();
+        assert!(!saved.contains("min_garbage_per_hole"));
+    }
+
+    #[test]
+    fn lists_theme_packs_after_the_built_in_themes() {
+        let built_in = MatchThemes::names().len();
+        assert_eq!(MatchThemes::Nes.menu_index(), 3);
+        assert_eq!(MatchThemes::Pack(1).menu_index(), built_in + 1);
+        for index in 0..built_in + 2 {
+            assert_eq!(MatchThemes::from_menu_index(index).menu_index(), index);
+        }
+        assert!(!MatchThemes::names().contains(&"Pack"));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::font::{FontTexture, FontType};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::ttf::{Font, Sdl2TtfContext};
+use sdl2::video::WindowContext;
+use crate::theme::helper::TextureFactory;
+use std::time::Duration;
+
+/// Go is shown for the end of the countdown, ready for the rest
+const GO_DURATION: Duration = Duration::from_millis(500);
+
+pub struct CountdownScreen<'a> {
+    ready: (Texture<'a>, Rect),
+    go: (Texture<'a>, Rect),
+}
+
+impl<'a> CountdownScreen<'a> {
+    pub fn new(
+        canvas: &mut WindowCanvas,
+        ttf: &Sdl2TtfContext,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        window_size: (u32, u32),
+    ) -> Result<Self, String> {
+        let font = FontType::Bold.load(ttf, window_size.0 / 16)?;
+        Ok(Self {
+            ready: Self::banner(canvas, texture_creator, &font, "READY", window_size)?,
+            go: Self::banner(canvas, texture_creator, &font, "GO!", window_size)?,
+        })
+    }
+
+    fn banner(
+        canvas: &mut WindowCanvas,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        font: &Font,
+        text: &str,
+        (window_width, window_height): (u32, u32),
+    ) -> Result<(Texture<'a>, Rect), String> {
+        let font_texture = FontTexture::from_string(font, texture_creator, text, Color::WHITE)?;
+        let padding = font_texture.height / 2;
+        let width = font_texture.width + 2 * padding;
+        let height = font_texture.height + padding;
+        let mut texture = texture_creator.create_texture_target_blended(width, height)?;
+        canvas
+            .with_texture_canvas(&mut texture, |c| {
+                c.set_draw_color(Color::RGBA(0, 0, 0, 0xcc));
+                c.clear();
+                let text_rect = Rect::from_center(
+                    (width as i32 / 2, height as i32 / 2),
+                    font_texture.width,
+                    font_texture.height,
+                );
+                c.copy(&font_texture.texture, None, text_rect).unwrap();
+            })
+            .map_err(|e| e.to_string())?;
+        let rect = Rect::from_center(
+            (window_width as i32 / 2, window_height as i32 / 2),
+            width,
+            height,
+        );
+        Ok((texture, rect))
+    }
+
+    pub fn draw(&self, canvas: &mut WindowCanvas, remaining: Duration) -> Result<(), String> {
+        let (texture, rect) = if remaining > GO_DURATION {
+            &self.ready
+        } else {
+            &self.go
+        };
+        canvas.copy(texture, None, *rect)
+    }
+}
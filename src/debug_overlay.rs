@@ -0,0 +1,208 @@
+use crate::font::FontTexture;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, TextureCreator, WindowCanvas};
+use sdl2::ttf::Font;
+use sdl2::video::WindowContext;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Frames kept for the stats, a couple of seconds at 60fps
+const HISTORY_FRAMES: usize = 120;
+/// The text is only rendered again this often so it stays readable
+const REFRESH_DURATION: Duration = Duration::from_millis(500);
+const BACKGROUND_COLOR: Color = Color::RGBA(0, 0, 0, 0xcc);
+
+/// Time spent in each phase of a single frame
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseTimings {
+    pub update: Duration,
+    pub render_to_texture: Duration,
+    pub particles: Duration,
+    pub present: Duration,
+}
+
+/// Times consecutive phases of a frame
+pub struct PhaseTimer {
+    t0: Instant,
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        Self { t0: Instant::now() }
+    }
+
+    /// Time since the last lap
+    pub fn lap(&mut self) -> Duration {
+        let now = Instant::now();
+        let lap = now - self.t0;
+        self.t0 = now;
+        lap
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct FrameStats {
+    frames: VecDeque<(Duration, PhaseTimings)>,
+}
+
+impl FrameStats {
+    fn push(&mut self, frame_time: Duration, phases: PhaseTimings) {
+        if self.frames.len() == HISTORY_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((frame_time, phases));
+    }
+
+    fn fps(&self) -> f64 {
+        let total = self.frames.iter().map(|(frame_time, _)| *frame_time).sum::<Duration>();
+        if total.is_zero() {
+            0.0
+        } else {
+            self.frames.len() as f64 / total.as_secs_f64()
+        }
+    }
+
+    /// Nearest rank percentile of the frame times
+    fn frame_time_percentile(&self, percentile: usize) -> Duration {
+        let mut frame_times = self
+            .frames
+            .iter()
+            .map(|(frame_time, _)| *frame_time)
+            .collect::<Vec<Duration>>();
+        if frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        frame_times.sort();
+        let rank = (percentile * frame_times.len()).div_ceil(100).max(1);
+        frame_times[rank - 1]
+    }
+
+    fn mean_phases(&self) -> PhaseTimings {
+        let frames = self.frames.len().max(1) as u32;
+        let sum = |phase: fn(&PhaseTimings) -> Duration| {
+            self.frames.iter().map(|(_, phases)| phase(phases)).sum::<Duration>() / frames
+        };
+        PhaseTimings {
+            update: sum(|p| p.update),
+            render_to_texture: sum(|p| p.render_to_texture),
+            particles: sum(|p| p.particles),
+            present: sum(|p| p.present),
+        }
+    }
+
+    fn lines(&self, particle_count: usize) -> Vec<String> {
+        let phases = self.mean_phases();
+        vec![
+            format!("fps {:.1}", self.fps()),
+            format!(
+                "frame p50 {} p95 {} p99 {}",
+                millis(self.frame_time_percentile(50)),
+                millis(self.frame_time_percentile(95)),
+                millis(self.frame_time_percentile(99)),
+            ),
+            format!("particles {}", particle_count),
+            format!("update {}", millis(phases.update)),
+            format!("render to texture {}", millis(phases.render_to_texture)),
+            format!("particles {}", millis(phases.particles)),
+            format!("present {}", millis(phases.present)),
+        ]
+    }
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Frame rate, frame time percentiles, particle count & phase timings drawn over a match
+pub struct DebugOverlay<'a> {
+    stats: FrameStats,
+    lines: Vec<FontTexture<'a>>,
+    since_refresh: Duration,
+}
+
+impl<'a> DebugOverlay<'a> {
+    pub fn new() -> Self {
+        Self {
+            stats: FrameStats::default(),
+            lines: vec![],
+            since_refresh: REFRESH_DURATION,
+        }
+    }
+
+    /// Records a finished frame, returns true when the text is due to be refreshed
+    pub fn push(&mut self, frame_time: Duration, phases: PhaseTimings) -> bool {
+        self.stats.push(frame_time, phases);
+        self.since_refresh += frame_time;
+        self.since_refresh >= REFRESH_DURATION
+    }
+
+    pub fn refresh(
+        &mut self,
+        font: &Font,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        particle_count: usize,
+    ) -> Result<(), String> {
+        self.since_refresh = Duration::ZERO;
+        self.lines = self
+            .stats
+            .lines(particle_count)
+            .iter()
+            .map(|line| FontTexture::from_string(font, texture_creator, line, Color::WHITE))
+            .collect::<Result<Vec<FontTexture>, String>>()?;
+        Ok(())
+    }
+
+    pub fn draw(&self, canvas: &mut WindowCanvas) -> Result<(), String> {
+        if self.lines.is_empty() {
+            return Ok(());
+        }
+        let padding = self.lines[0].height / 4;
+        let width = self.lines.iter().map(|line| line.width).max().unwrap();
+        let height = self.lines.iter().map(|line| line.height).sum::<u32>();
+        canvas.set_blend_mode(BlendMode::Blend);
+        canvas.set_draw_color(BACKGROUND_COLOR);
+        canvas.fill_rect(Rect::new(0, 0, width + 2 * padding, height + 2 * padding))?;
+        canvas.set_blend_mode(BlendMode::None);
+
+        let mut y = padding as i32;
+        for line in self.lines.iter() {
+            let rect = Rect::new(padding as i32, y, line.width, line.height);
+            canvas.copy(&line.texture, None, rect)?;
+            y += line.height as i32;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(frame_times_ms: &[u64]) -> FrameStats {
+        let mut stats = FrameStats::default();
+        for frame_time in frame_times_ms {
+            stats.push(Duration::from_millis(*frame_time), PhaseTimings::default());
+        }
+        stats
+    }
+
+    #[test]
+    fn calculates_frame_time_percentiles() {
+        let stats = stats(&(1..=100).collect::<Vec<u64>>());
+        assert_eq!(stats.frame_time_percentile(50), Duration::from_millis(50));
+        assert_eq!(stats.frame_time_percentile(99), Duration::from_millis(99));
+        assert_eq!(stats.frame_time_percentile(0), Duration::from_millis(1));
+        assert_eq!(FrameStats::default().frame_time_percentile(50), Duration::ZERO);
+    }
+
+    #[test]
+    fn calculates_fps_over_recent_frames() {
+        let mut stats = stats(&[1000; HISTORY_FRAMES]);
+        for _ in 0..HISTORY_FRAMES {
+            stats.push(Duration::from_millis(20), PhaseTimings::default());
+        }
+        assert_eq!(stats.frames.len(), HISTORY_FRAMES);
+        assert!((stats.fps() - 50.0).abs() < 1e-6);
+    }
+}
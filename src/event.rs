@@ -29,6 +29,7 @@ pub enum GameEvent {
         level_up: bool,
     },
     Hold,
+    Undo,
     Paused,
     UnPaused,
     GameOver {
@@ -41,6 +42,9 @@ pub enum GameEvent {
     Quit,
     ReturnToMenu,
     NextTheme,
+    ExportBoard,
+    ImportBoard,
+    Restart,
     ReceivedGarbage {
         player: u32,
         lines: u32,
@@ -68,4 +72,6 @@ pub enum GameOverCondition {
     LockOut,
     /// Block Out: One of the starting cells of the Next Tetrimino is blocked by an existing Block
     BlockOut,
+    /// Out of Pieces: A puzzle runs out of tetrominos before its goal is met
+    OutOfPieces,
 }
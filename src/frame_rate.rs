@@ -1,23 +1,80 @@
-use std::time::{Duration, SystemTime};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Sleeping overshoots by up to the scheduler's resolution so the end of a wait is spun instead
+const SPIN_DURATION: Duration = Duration::from_millis(2);
 
 #[derive(Debug, Copy, Clone)]
 pub struct FrameRate {
-    t0: SystemTime,
+    t0: Instant,
+    min_frame_time: Option<Duration>,
 }
 
 impl FrameRate {
-    pub fn new() -> Self {
+    /// Frames are limited to max_fps when set
+    pub fn new(max_fps: Option<u32>) -> Self {
         Self {
-            t0: SystemTime::now(),
+            t0: Instant::now(),
+            min_frame_time: max_fps
+                .filter(|fps| *fps > 0)
+                .map(|fps| Duration::from_secs(1) / fps),
         }
     }
 
-    /// Registers the start of a new frame, returns the time since the last frame
-    pub fn update(&mut self) -> Result<Duration, String> {
-        // TODO have option of limiting/recording the effective framerate
-        let now = SystemTime::now();
-        let delta = now.duration_since(self.t0).map_err(|e| e.to_string())?;
+    /// Registers the start of a new frame, waiting out the frame rate limit,
+    /// returns the time since the last frame
+    pub fn update(&mut self) -> Duration {
+        if let Some(deadline) = self.deadline() {
+            wait_until(deadline);
+        }
+        let now = Instant::now();
+        let delta = now - self.t0;
         self.t0 = now;
-        Ok(delta)
+        delta
+    }
+
+    /// When the next frame may start, None when the frame rate is unlimited
+    fn deadline(&self) -> Option<Instant> {
+        self.min_frame_time
+            .map(|min_frame_time| self.t0 + min_frame_time)
+    }
+}
+
+fn wait_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now + SPIN_DURATION {
+        thread::sleep(deadline - now - SPIN_DURATION);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_the_frame_rate() {
+        let frame_rate = FrameRate::new(Some(100));
+        assert_eq!(
+            frame_rate.deadline(),
+            Some(frame_rate.t0 + Duration::from_millis(10))
+        );
+    }
+
+    #[test]
+    fn waits_until_the_deadline() {
+        // only the lower bound holds however busy the scheduler is
+        let deadline = Instant::now() + Duration::from_millis(5);
+        wait_until(deadline);
+        assert!(Instant::now() >= deadline);
+    }
+
+    #[test]
+    fn unlimited_without_max_fps() {
+        for max_fps in [None, Some(0)] {
+            assert_eq!(FrameRate::new(max_fps).deadline(), None);
+        }
     }
 }
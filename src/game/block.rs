@@ -1,7 +1,8 @@
 use super::tetromino::TetrominoShape;
 use crate::game::geometry::Rotation;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BlockState {
     Empty,
     Tetromino(TetrominoShape, Rotation, u32),
@@ -1,7 +1,9 @@
 use super::block::BlockState;
 use super::geometry::Point;
+use super::rotation::RotationSystem;
 use super::tetromino::{Tetromino, TetrominoShape};
 use crate::game::tetromino::Minos;
+use serde::{Deserialize, Serialize};
 
 use std::fmt::{Display, Formatter};
 
@@ -24,9 +26,34 @@ pub fn compact_destroy_lines(lines: DestroyLines) -> Vec<u32> {
         .collect()
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Board {
+    #[serde(with = "blocks_as_vec")]
     blocks: [BlockState; TOTAL_BLOCKS as usize],
     tetromino: Option<Tetromino>,
+    rotation_system: RotationSystem,
+    rotated_last: bool,
+}
+
+pub type StackRow = [BlockState; BOARD_WIDTH as usize];
+
+/// serde only supports arrays of up to 32 elements
+mod blocks_as_vec {
+    use super::{BlockState, TOTAL_BLOCKS};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    type Blocks = [BlockState; TOTAL_BLOCKS as usize];
+
+    pub fn serialize<S: Serializer>(blocks: &Blocks, s: S) -> Result<S::Ok, S::Error> {
+        blocks.as_slice().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Blocks, D::Error> {
+        Vec::<BlockState>::deserialize(d)?
+            .try_into()
+            .map_err(|_| D::Error::custom(format!("expected {} blocks", TOTAL_BLOCKS)))
+    }
 }
 
 fn index_at(x: u32, y: u32) -> usize {
@@ -47,10 +74,12 @@ fn rows_range(y_from: u32, y_to: u32) -> Range<usize> {
 }
 
 impl Board {
-    pub fn new() -> Self {
+    pub fn new(rotation_system: RotationSystem) -> Self {
         Self {
             blocks: [BlockState::Empty; TOTAL_BLOCKS as usize],
             tetromino: None,
+            rotation_system,
+            rotated_last: false,
         }
     }
 
@@ -64,6 +93,10 @@ impl Board {
         }
     }
 
+    pub fn tetromino(&self) -> Option<Tetromino> {
+        self.tetromino
+    }
+
     pub fn block(&self, point: Point) -> BlockState {
         self.blocks[index(point)]
     }
@@ -72,8 +105,16 @@ impl Board {
         self.blocks[index(point)] = state;
     }
 
+    pub fn rotation_system(&self) -> RotationSystem {
+        self.rotation_system
+    }
+
     pub fn try_spawn_tetromino(&mut self, shape: TetrominoShape) -> Option<Minos> {
-        let tetromino = Tetromino::new(shape);
+        self.try_place_tetromino(Tetromino::new(shape, self.rotation_system))
+    }
+
+    /// Puts a tetromino in play wherever it is, e.g. one imported from a fumen
+    pub fn try_place_tetromino(&mut self, tetromino: Tetromino) -> Option<Minos> {
         if self.tetromino.is_some() {
             panic!("tetromino already spawned")
         }
@@ -86,13 +127,18 @@ impl Board {
             } else {
                 self.set_block(
                     p,
-                    BlockState::Tetromino(tetromino.shape(), tetromino.rotation(), id as u32),
+                    BlockState::Tetromino(
+                        tetromino.shape(),
+                        tetromino.sprite_rotation(),
+                        id as u32,
+                    ),
                 );
             }
         }
 
         // regardless of success we have set blocks for this tetromino
         self.tetromino = Some(tetromino);
+        self.rotated_last = false;
 
         if success {
             self.render_ghost();
@@ -122,6 +168,7 @@ impl Board {
         }
 
         self.mutate_tetromino(|t| t.translate(-1, 0));
+        self.rotated_last = false;
         true
     }
 
@@ -145,6 +192,7 @@ impl Board {
         }
 
         self.mutate_tetromino(|t| t.translate(1, 0));
+        self.rotated_last = false;
         true
     }
 
@@ -163,7 +211,11 @@ impl Board {
         for (id, p) in tetromino.minos().into_iter().enumerate() {
             self.set_block(
                 p,
-                BlockState::Tetromino(tetromino.shape(), tetromino.rotation(), id as u32),
+                BlockState::Tetromino(
+                    tetromino.shape(),
+                    tetromino.sprite_rotation(),
+                    id as u32,
+                ),
             );
         }
 
@@ -198,28 +250,43 @@ impl Board {
             if self.block(p) == BlockState::Empty {
                 self.set_block(
                     p,
-                    BlockState::Ghost(tetromino.shape(), tetromino.rotation(), id as u32),
+                    BlockState::Ghost(
+                        tetromino.shape(),
+                        tetromino.sprite_rotation(),
+                        id as u32,
+                    ),
                 )
             }
         }
     }
 
     pub fn rotate(&mut self, clockwise: bool) -> bool {
-        let wall_kick_id = self.try_rotate(clockwise);
+        let wall_kick_id = self.try_rotate(|t| t.possible_minos_after_rotation(clockwise));
         if wall_kick_id.is_none() {
             return false;
         }
         self.mutate_tetromino(|tetromino| tetromino.rotate(clockwise, wall_kick_id.unwrap()));
+        self.rotated_last = true;
         true
     }
 
-    fn try_rotate(&self, clockwise: bool) -> Option<usize> {
+    pub fn half_turn(&mut self) -> bool {
+        let wall_kick_id = self.try_rotate(|t| t.possible_minos_after_half_turn());
+        if wall_kick_id.is_none() {
+            return false;
+        }
+        self.mutate_tetromino(|tetromino| tetromino.half_turn(wall_kick_id.unwrap()));
+        self.rotated_last = true;
+        true
+    }
+
+    fn try_rotate<F>(&self, possible_minos: F) -> Option<usize>
+    where
+        F: Fn(&Tetromino) -> Vec<Minos>,
+    {
         self.tetromino?;
 
-        let next_minos = self
-            .tetromino
-            .unwrap()
-            .possible_minos_after_rotation(clockwise);
+        let next_minos = possible_minos(&self.tetromino.unwrap());
         for (id, minos) in next_minos.iter().enumerate() {
             let mut success = true;
             for p in minos {
@@ -289,6 +356,7 @@ impl Board {
         }
 
         self.mutate_tetromino(|t| t.translate(0, -1));
+        self.rotated_last = false;
         true
     }
 
@@ -309,12 +377,47 @@ impl Board {
         let original_minos = self.tetromino.unwrap().minos();
         if hard_dropped_rows > 0 {
             self.mutate_tetromino(|t| t.translate(0, -hard_dropped_rows));
+            self.rotated_last = false;
             Some((hard_dropped_rows as u32, original_minos))
         } else {
             None
         }
     }
 
+    /// The current tetromino is a T that was rotated into place
+    /// with at least 3 of the 4 corners around its center occupied
+    pub fn is_t_spin(&self) -> bool {
+        let tetromino = match self.tetromino {
+            Some(t) if t.shape() == TetrominoShape::T && self.rotated_last => t,
+            _ => return false,
+        };
+        let minos = tetromino.minos();
+        // the center is the only mino touching all 3 others
+        let center = minos
+            .iter()
+            .find(|p| {
+                minos
+                    .iter()
+                    .filter(|q| (p.x - q.x).abs() + (p.y - q.y).abs() == 1)
+                    .count()
+                    == 3
+            })
+            .unwrap();
+        let corners = [(-1, -1), (1, -1), (-1, 1), (1, 1)]
+            .into_iter()
+            .filter(|(x, y)| self.is_occupied(center.translate(*x, *y)))
+            .count();
+        corners >= 3
+    }
+
+    /// Walls & the floor count as occupied
+    fn is_occupied(&self, p: Point) -> bool {
+        if p.x < 0 || p.x >= BOARD_WIDTH as i32 || p.y < 0 {
+            return true;
+        }
+        p.y < TOTAL_HEIGHT as i32 && self.block(p).collides()
+    }
+
     /// Locks the current tetromino
     pub fn lock(&mut self) -> Option<Minos> {
         self.tetromino?;
@@ -322,7 +425,11 @@ impl Board {
         for (id, p) in tetromino.minos().into_iter().enumerate() {
             self.set_block(
                 p,
-                BlockState::Stack(tetromino.shape(), tetromino.rotation(), id as u32),
+                BlockState::Stack(
+                    tetromino.shape(),
+                    tetromino.sprite_rotation(),
+                    id as u32,
+                ),
             );
         }
         let result = tetromino.minos();
@@ -365,6 +472,25 @@ impl Board {
         true
     }
 
+    /// Removes every block and any tetromino from the board
+    pub fn clear(&mut self) {
+        self.blocks = [BlockState::Empty; TOTAL_BLOCKS as usize];
+        self.tetromino = None;
+        self.rotated_last = false;
+    }
+
+    /// Replaces the bottom rows of the board with a preset stack, listed from the bottom up
+    pub fn preset_stack(&mut self, rows: &[StackRow]) {
+        for (y, row) in rows.iter().enumerate() {
+            self.blocks[row_range(y as u32)].copy_from_slice(row);
+        }
+        self.render_ghost();
+    }
+
+    pub fn is_stack_empty(&self) -> bool {
+        !self.blocks.iter().any(|b| b.collides())
+    }
+
     pub fn hold(&mut self) -> Option<TetrominoShape> {
         self.tetromino?;
 
@@ -409,6 +535,21 @@ impl Board {
             .all(|mino| mino.y >= BOARD_HEIGHT as i32)
     }
 
+    /// Number of rows that still contain any garbage
+    pub fn garbage_rows(&self) -> u32 {
+        (0..TOTAL_HEIGHT)
+            .filter(|y| self.row(*y).contains(&BlockState::Garbage))
+            .count() as u32
+    }
+
+    /// Number of rows up to the highest stack block, 0 when the stack is empty
+    pub fn stack_height(&self) -> u32 {
+        (0..TOTAL_HEIGHT)
+            .rev()
+            .find(|y| self.row(*y).iter().any(|b| b.collides()))
+            .map_or(0, |y| y + 1)
+    }
+
     pub fn is_stack_above_skyline(&self) -> bool {
         for block in &self.blocks[rows_range(BOARD_HEIGHT, TOTAL_HEIGHT - 1)] {
             if block.collides() {
@@ -457,7 +598,7 @@ mod tests {
             $(
                 #[test]
                 fn $name() {
-                    let mut board = Board::new();
+                    let mut board = Board::new(RotationSystem::Srs);
                     can_spawn_tetromino(&mut board, $shape);
                     should_have_tetromino_at(&board, &$points);
                     should_have_n_tetromino_blocks(&board, 4);
@@ -526,7 +667,7 @@ mod tests {
 
     #[test]
     fn steps_down() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::J);
         assert!(board.step_down());
         should_have_tetromino_at(
@@ -542,7 +683,7 @@ mod tests {
 
     #[test]
     fn steps_down_to_floor() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::L);
         should_collide_after_step_downs(&mut board, BOARD_HEIGHT);
 
@@ -559,7 +700,7 @@ mod tests {
 
     #[test]
     fn locks() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::L);
         having_step_downs(&mut board, BOARD_HEIGHT);
         board.lock();
@@ -576,7 +717,7 @@ mod tests {
 
     #[test]
     fn steps_down_to_stack() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         can_spawn_tetromino(&mut board, TetrominoShape::I);
         should_collide_after_step_downs(&mut board, BOARD_HEIGHT - 1);
@@ -594,7 +735,7 @@ mod tests {
 
     #[test]
     fn moves_left() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         having_step_downs(&mut board, 2); // into board
         assert!(board.left(), "{}", board);
@@ -611,7 +752,7 @@ mod tests {
 
     #[test]
     fn cannot_move_left_through_wall() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         for i in 0..4 {
             assert!(board.left(), "{}: {}", i, board)
@@ -630,7 +771,7 @@ mod tests {
 
     #[test]
     fn cannot_move_left_through_stack() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_col(&mut board, 0);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         having_step_downs(&mut board, 1); // peeking into board i.e. will only collide with a single mino
@@ -651,7 +792,7 @@ mod tests {
 
     #[test]
     fn moves_right() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         having_step_downs(&mut board, 2); // into board
         assert!(board.right(), "{}", board);
@@ -668,7 +809,7 @@ mod tests {
 
     #[test]
     fn cannot_move_right_through_wall() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         for i in 0..4 {
             assert!(board.right(), "{}: {}", i, board)
@@ -687,7 +828,7 @@ mod tests {
 
     #[test]
     fn cannot_move_right_through_stack() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_col(&mut board, 9);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         having_step_downs(&mut board, 1); // peeking into board i.e. will only collide with a single mino
@@ -740,7 +881,7 @@ mod tests {
 
     #[test]
     fn rotating_o_does_nothing() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         assert!(board.rotate(true));
         should_have_tetromino_at(
@@ -757,7 +898,7 @@ mod tests {
 
     #[test]
     fn rotating_l() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::L);
         assert!(board.rotate(true));
         should_have_tetromino_at(
@@ -772,9 +913,43 @@ mod tests {
         should_have_n_tetromino_blocks(&board, 4);
     }
 
+    #[test]
+    fn half_turn_l() {
+        let mut board = Board::new(RotationSystem::Srs);
+        can_spawn_tetromino(&mut board, TetrominoShape::L);
+        assert!(board.half_turn());
+        should_have_tetromino_at(
+            &board,
+            &[
+                Point::new(5, 20),
+                Point::new(4, 20),
+                Point::new(3, 20),
+                Point::new(3, 19),
+            ],
+        );
+        should_have_n_tetromino_blocks(&board, 4);
+    }
+
+    #[test]
+    fn half_turn_kicks_off_floor() {
+        let mut board = Board::new(RotationSystem::Srs);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        having_step_downs(&mut board, BOARD_HEIGHT);
+        assert!(board.half_turn());
+        should_have_tetromino_at(
+            &board,
+            &[
+                Point::new(5, 1),
+                Point::new(4, 1),
+                Point::new(3, 1),
+                Point::new(4, 0),
+            ],
+        );
+    }
+
     #[test]
     fn rotating_i_off_floor() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::I);
         having_step_downs(&mut board, BOARD_HEIGHT);
         assert!(board.rotate(true));
@@ -792,14 +967,14 @@ mod tests {
 
     #[test]
     fn no_patterns_on_empty_board() {
-        let board = Board::new();
+        let board = Board::new(RotationSystem::Srs);
         let observed = board.pattern();
         assert_eq!(observed, NO_DESTROYED_LINES);
     }
 
     #[test]
     fn no_patterns() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_at(&mut board, 0, 0);
         let observed = board.pattern();
         assert_eq!(observed, NO_DESTROYED_LINES);
@@ -807,7 +982,7 @@ mod tests {
 
     #[test]
     fn single_line_pattern() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         having_stack_at(&mut board, 0, 1);
         let observed = board.pattern();
@@ -816,7 +991,7 @@ mod tests {
 
     #[test]
     fn double_line_pattern() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         having_stack_row(&mut board, 1);
         having_stack_at(&mut board, 0, 2);
@@ -826,7 +1001,7 @@ mod tests {
 
     #[test]
     fn triple_line_pattern() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         having_stack_row(&mut board, 1);
         having_stack_row(&mut board, 2);
@@ -837,7 +1012,7 @@ mod tests {
 
     #[test]
     fn triple_line_pattern_with_separation() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         having_stack_row(&mut board, 1);
         having_stack_row(&mut board, 3);
@@ -848,7 +1023,7 @@ mod tests {
 
     #[test]
     fn tetris_line_pattern() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         having_stack_row(&mut board, 1);
         having_stack_row(&mut board, 2);
@@ -860,7 +1035,7 @@ mod tests {
 
     #[test]
     fn destroy_single_line() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         having_stack_at(&mut board, 0, 1);
         having_stack_at(&mut board, 0, 2);
@@ -870,7 +1045,7 @@ mod tests {
 
     #[test]
     fn hard_drops_onto_floor() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         assert_eq!(board.hard_drop().map(|(y, _)| y), Some(20));
         should_have_tetromino_at(
@@ -886,7 +1061,7 @@ mod tests {
 
     #[test]
     fn hard_drops_onto_stack() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_row(&mut board, 0);
         can_spawn_tetromino(&mut board, TetrominoShape::O);
         assert_eq!(board.hard_drop().map(|(y, _)| y), Some(19));
@@ -903,7 +1078,7 @@ mod tests {
 
     #[test]
     fn holds_tetromino() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::I);
         assert_eq!(board.hold(), Some(TetrominoShape::I));
         should_have_empty_board(&board);
@@ -912,7 +1087,7 @@ mod tests {
 
     #[test]
     fn holds_nothing() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         assert_eq!(board.hold(), None);
         should_have_empty_board(&board);
         assert!(board.tetromino.is_none());
@@ -920,7 +1095,7 @@ mod tests {
 
     #[test]
     fn sends_garbage() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         having_stack_at(&mut board, 4, 1);
         having_stack_at(&mut board, 5, 0);
         board.send_garbage(5);
@@ -936,9 +1111,92 @@ mod tests {
         }
     }
 
+    #[test]
+    fn counts_garbage_rows() {
+        let mut board = Board::new(RotationSystem::Srs);
+        having_stack_row(&mut board, 0);
+        board.send_garbage(2);
+        board.send_garbage(7);
+        assert_eq!(board.garbage_rows(), 2);
+        board.destroy([Some(0), None, None, None]);
+        assert_eq!(board.garbage_rows(), 1);
+    }
+
+    #[test]
+    fn clears_board() {
+        let mut board = Board::new(RotationSystem::Srs);
+        having_stack_row(&mut board, 0);
+        board.send_garbage(3);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        board.clear();
+        assert!(board.tetromino.is_none());
+        assert!(board.blocks.iter().all(|b| *b == BlockState::Empty));
+    }
+
+    #[test]
+    fn presets_stack() {
+        let mut board = Board::new(RotationSystem::Srs);
+        let mut row = [BlockState::Garbage; BOARD_WIDTH as usize];
+        row[4] = BlockState::Empty;
+        board.preset_stack(&[row, row]);
+        assert_eq!(board.row(0), row);
+        assert_eq!(board.row(1), row);
+        assert!(!board.is_stack_empty());
+        board.destroy([Some(0), Some(1), None, None]);
+        assert!(board.is_stack_empty());
+    }
+
+    #[test]
+    fn measures_stack_height() {
+        let mut board = Board::new(RotationSystem::Srs);
+        assert_eq!(board.stack_height(), 0);
+        having_stack_row(&mut board, 0);
+        having_stack_at(&mut board, 2, 5);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        assert_eq!(board.stack_height(), 6);
+        board.send_garbage(3);
+        assert_eq!(board.stack_height(), 7);
+    }
+
+    fn having_t_slot(board: &mut Board) {
+        // T spin double slot at x=4 with an overhang at (3, 2)
+        for x in (0..BOARD_WIDTH).filter(|x| *x != 4) {
+            having_stack_at(board, x, 0);
+        }
+        for x in (0..BOARD_WIDTH).filter(|x| !(3..=5).contains(x)) {
+            having_stack_at(board, x, 1);
+        }
+        for x in 0..4 {
+            having_stack_at(board, x, 2);
+        }
+    }
+
+    #[test]
+    fn t_spin() {
+        let mut board = Board::new(RotationSystem::Srs);
+        having_t_slot(&mut board);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        assert!(board.rotate(true));
+        assert!(board.hard_drop().is_some());
+        assert!(!board.is_t_spin());
+        assert!(board.rotate(true));
+        assert!(board.is_t_spin(), "{}", board);
+        board.lock();
+        assert_eq!(compact_destroy_lines(board.pattern()), vec![0, 1]);
+    }
+
+    #[test]
+    fn dropped_t_is_not_t_spin() {
+        let mut board = Board::new(RotationSystem::Srs);
+        having_stack_row(&mut board, 0);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        assert!(board.hard_drop().is_some());
+        assert!(!board.is_t_spin());
+    }
+
     #[test]
     fn is_tetromino_above_skyline() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         can_spawn_tetromino(&mut board, TetrominoShape::I);
         assert!(board.is_tetromino_above_skyline(), "{}", board);
         assert!(board.step_down());
@@ -947,9 +1205,75 @@ mod tests {
 
     #[test]
     fn is_stack_above_skyline() {
-        let mut board = Board::new();
+        let mut board = Board::new(RotationSystem::Srs);
         assert!(!board.is_stack_above_skyline(), "{}", board);
         having_stack_row(&mut board, BOARD_HEIGHT);
         assert!(board.is_stack_above_skyline(), "{}", board);
     }
+
+    #[test]
+    fn spawns_ars_flat_side_up() {
+        let mut board = Board::new(RotationSystem::Ars);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        should_have_tetromino_at(
+            &board,
+            &[
+                Point::new(3, 21),
+                Point::new(4, 21),
+                Point::new(5, 21),
+                Point::new(4, 20),
+            ],
+        );
+    }
+
+    #[test]
+    fn spawns_classic_right_handed() {
+        let mut board = Board::new(RotationSystem::Classic);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        should_have_tetromino_at(
+            &board,
+            &[
+                Point::new(4, 21),
+                Point::new(5, 21),
+                Point::new(6, 21),
+                Point::new(5, 20),
+            ],
+        );
+    }
+
+    #[test]
+    fn ars_kicks_off_right_wall() {
+        assert!(rotate_t_against_right_wall(RotationSystem::Ars));
+    }
+
+    #[test]
+    fn classic_cannot_kick_off_right_wall() {
+        assert!(!rotate_t_against_right_wall(RotationSystem::Classic));
+    }
+
+    fn rotate_t_against_right_wall(rotation_system: RotationSystem) -> bool {
+        let mut board = Board::new(rotation_system);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        assert!(board.rotate(true), "{}", board);
+        while board.right() {}
+        board.rotate(false)
+    }
+
+    #[test]
+    fn round_trips_through_a_save() {
+        let mut board = Board::new(RotationSystem::Srs);
+        board.send_garbage(3);
+        board.send_garbage(7);
+        can_spawn_tetromino(&mut board, TetrominoShape::T);
+        board.rotate(true);
+
+        let yaml = serde_yaml::to_string(&board).unwrap();
+        let loaded = serde_yaml::from_str::<Board>(&yaml).unwrap();
+        assert_eq!(loaded.blocks, board.blocks, "{}", loaded);
+        assert_eq!(loaded.tetromino(), board.tetromino());
+        assert_eq!(loaded.garbage_rows(), 2);
+
+        let truncated = yaml.replacen("- Empty\n", "", 1);
+        assert!(serde_yaml::from_str::<Board>(&truncated).is_err());
+    }
 }
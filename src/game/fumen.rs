@@ -0,0 +1,703 @@
+use super::block::BlockState;
+use super::board::{Board, StackRow, BOARD_WIDTH};
+use super::geometry::{Point, Rotation};
+use super::rotation::RotationSystem;
+use super::tetromino::{Minos, Tetromino, TetrominoShape};
+
+const VERSION_PREFIX: &str = "v115@";
+const ENCODE_TABLE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const COMMENT_TABLE: &[u8; 95] = b" !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+const COMMENT_CHAR_VALUES: u32 = COMMENT_TABLE.len() as u32 + 1;
+const QUEUE_PREFIX: &str = "#Q=";
+
+// the data is split by a '?' so that including the prefix every line is 47 characters
+const FIRST_LINE_LENGTH: usize = 42;
+const LINE_LENGTH: usize = 47;
+
+/// Rows of a fumen field, there is also a garbage row below the floor that is ignored here
+pub const FUMEN_HEIGHT: u32 = 23;
+const FIELD_BLOCKS: u32 = (FUMEN_HEIGHT + 1) * BOARD_WIDTH;
+const GARBAGE_CODE: u32 = 8;
+const DIFF_OFFSET: u32 = 8;
+
+fn shape_code(shape: TetrominoShape) -> u32 {
+    match shape {
+        TetrominoShape::I => 1,
+        TetrominoShape::L => 2,
+        TetrominoShape::O => 3,
+        TetrominoShape::Z => 4,
+        TetrominoShape::T => 5,
+        TetrominoShape::J => 6,
+        TetrominoShape::S => 7,
+    }
+}
+
+fn code_shape(code: u32) -> Option<TetrominoShape> {
+    TetrominoShape::ALL.into_iter().find(|s| shape_code(*s) == code)
+}
+
+pub fn shape_char(shape: TetrominoShape) -> char {
+    match shape {
+        TetrominoShape::I => 'I',
+        TetrominoShape::O => 'O',
+        TetrominoShape::T => 'T',
+        TetrominoShape::S => 'S',
+        TetrominoShape::Z => 'Z',
+        TetrominoShape::J => 'J',
+        TetrominoShape::L => 'L',
+    }
+}
+
+pub fn char_shape(c: char) -> Option<TetrominoShape> {
+    TetrominoShape::ALL.into_iter().find(|s| shape_char(*s) == c)
+}
+
+fn block_code(block: BlockState) -> u32 {
+    match block {
+        BlockState::Stack(shape, _, _) => shape_code(shape),
+        BlockState::Garbage => GARBAGE_CODE,
+        _ => 0,
+    }
+}
+
+fn code_block(code: u32) -> BlockState {
+    match code_shape(code) {
+        Some(shape) => BlockState::Stack(shape, Rotation::North, 0),
+        None if code == GARBAGE_CODE => BlockState::Garbage,
+        None => BlockState::Empty,
+    }
+}
+
+fn rotation_code(rotation: Rotation) -> u32 {
+    match rotation {
+        Rotation::South => 0,
+        Rotation::East => 1,
+        Rotation::North => 2,
+        Rotation::West => 3,
+    }
+}
+
+fn code_rotation(code: u32) -> Rotation {
+    match code {
+        0 => Rotation::South,
+        1 => Rotation::East,
+        2 => Rotation::North,
+        _ => Rotation::West,
+    }
+}
+
+/// Minos of each shape in its spawn rotation relative to its center, as fumen defines them
+fn spawn_offsets(shape: TetrominoShape) -> [(i32, i32); 4] {
+    match shape {
+        TetrominoShape::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+        TetrominoShape::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        TetrominoShape::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+        TetrominoShape::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        TetrominoShape::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+        TetrominoShape::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+        TetrominoShape::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+    }
+}
+
+/// Fumen stores some rotations of the symmetrical shapes from a different center
+fn position_adjustment(shape: TetrominoShape, rotation: Rotation) -> Point {
+    match (shape, rotation) {
+        (TetrominoShape::O, Rotation::West) => Point::new(1, -1),
+        (TetrominoShape::O, Rotation::South) => Point::new(1, 0),
+        (TetrominoShape::O, Rotation::North) => Point::new(0, -1),
+        (TetrominoShape::I, Rotation::South) => Point::new(1, 0),
+        (TetrominoShape::I, Rotation::West) => Point::new(0, -1),
+        (TetrominoShape::S, Rotation::North) => Point::new(0, -1),
+        (TetrominoShape::S, Rotation::East) => Point::new(-1, 0),
+        (TetrominoShape::Z, Rotation::North) => Point::new(0, -1),
+        (TetrominoShape::Z, Rotation::West) => Point::new(1, 0),
+        _ => Point::new(0, 0),
+    }
+}
+
+/// A tetromino placed on a fumen page
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FumenPiece {
+    pub shape: TetrominoShape,
+    pub rotation: Rotation,
+    pub center: Point,
+}
+
+impl FumenPiece {
+    pub fn minos(&self) -> Minos {
+        spawn_offsets(self.shape).map(|(x, y)| {
+            let offset = Point::new(x, y);
+            let rotated = match self.rotation {
+                Rotation::North => offset,
+                Rotation::East => offset.rotate(true),
+                Rotation::South => offset.rotate(true).rotate(true),
+                Rotation::West => offset.rotate(false),
+            };
+            rotated + self.center
+        })
+    }
+
+    /// Finds the fumen rotation & center of a tetromino from its minos,
+    /// so it does not matter which rotation system placed it
+    fn from_minos(shape: TetrominoShape, minos: Minos) -> Option<Self> {
+        let sorted = |mut minos: Minos| {
+            minos.sort_by_key(|p| (p.x, p.y));
+            minos
+        };
+        let target = sorted(minos);
+        let min_x = target.iter().map(|p| p.x).min()?;
+        let min_y = target.iter().map(|p| p.y).min()?;
+        [Rotation::North, Rotation::East, Rotation::South, Rotation::West]
+            .into_iter()
+            .find_map(|rotation| {
+                let origin = Self {
+                    shape,
+                    rotation,
+                    center: Point::new(0, 0),
+                }
+                .minos();
+                let center = Point::new(
+                    min_x - origin.iter().map(|p| p.x).min().unwrap(),
+                    min_y - origin.iter().map(|p| p.y).min().unwrap(),
+                );
+                let piece = Self {
+                    shape,
+                    rotation,
+                    center,
+                };
+                (sorted(piece.minos()) == target).then_some(piece)
+            })
+    }
+
+    fn position(&self) -> u32 {
+        let p = self.center + position_adjustment(self.shape, self.rotation);
+        (FUMEN_HEIGHT as i32 - p.y - 1) as u32 * BOARD_WIDTH + p.x as u32
+    }
+
+    fn from_position(shape: TetrominoShape, rotation: Rotation, position: u32) -> Self {
+        let x = (position % BOARD_WIDTH) as i32;
+        let y = FUMEN_HEIGHT as i32 - (position / BOARD_WIDTH) as i32 - 1;
+        let adjustment = position_adjustment(shape, rotation);
+        Self {
+            shape,
+            rotation,
+            center: Point::new(x - adjustment.x, y - adjustment.y),
+        }
+    }
+}
+
+/// The first page of a fumen: the stack, the tetromino in play and the queue from its comment
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FumenPage {
+    /// Rows of the stack from the bottom up
+    pub field: Vec<StackRow>,
+    pub piece: Option<FumenPiece>,
+    pub hold: Option<TetrominoShape>,
+    pub current: Option<TetrominoShape>,
+    pub queue: Vec<TetrominoShape>,
+}
+
+impl FumenPage {
+    /// The stack & tetromino in play on a board
+    pub fn from_board(board: &Board) -> Self {
+        let field = (0..FUMEN_HEIGHT)
+            .map(|y| {
+                let mut row = [BlockState::Empty; BOARD_WIDTH as usize];
+                for (block, board_block) in row.iter_mut().zip(board.row(y)) {
+                    if board_block.collides() {
+                        *block = *board_block;
+                    }
+                }
+                row
+            })
+            .collect();
+        let piece = board
+            .tetromino()
+            .and_then(|t| FumenPiece::from_minos(t.shape(), t.minos()));
+        Self {
+            field,
+            piece,
+            current: piece.map(|p| p.shape),
+            ..Default::default()
+        }
+    }
+
+    /// A board with the stack & tetromino in play on this page
+    pub fn to_board(&self, rotation_system: RotationSystem) -> Result<Board, String> {
+        let mut board = Board::new(rotation_system);
+        board.preset_stack(&self.field);
+        if let Some(piece) = self.piece {
+            let minos = piece.minos();
+            if minos.iter().any(|p| p.x < 0 || p.x >= BOARD_WIDTH as i32 || p.y < 0) {
+                return Err("fumen piece is off the board".to_string());
+            }
+            let tetromino = Tetromino::from_minos(piece.shape, rotation_system, minos)
+                .ok_or("bad fumen piece")?;
+            board
+                .try_place_tetromino(tetromino)
+                .ok_or("fumen piece overlaps the stack")?;
+        }
+        Ok(board)
+    }
+
+    /// Queue in the comment format shared by fumen tools e.g. "#Q=[T](I)OSZ"
+    fn queue_comment(&self) -> Option<String> {
+        if self.hold.is_none() && self.current.is_none() && self.queue.is_empty() {
+            return None;
+        }
+        let mut result = QUEUE_PREFIX.to_string();
+        if let Some(hold) = self.hold {
+            result.push_str(&format!("[{}]", shape_char(hold)));
+        }
+        if let Some(current) = self.current {
+            result.push_str(&format!("({})", shape_char(current)));
+        }
+        result.extend(self.queue.iter().map(|s| shape_char(*s)));
+        Some(result)
+    }
+
+    /// Sets the hold, current & queue from a queue comment, any other comment is ignored
+    fn read_queue_comment(&mut self, comment: &str) {
+        let mut chars = match comment.strip_prefix(QUEUE_PREFIX) {
+            Some(queue) => queue.chars().peekable(),
+            None => return,
+        };
+        let mut bracketed = |open: char, close: char| {
+            chars.next_if_eq(&open)?;
+            let shape = chars.next().and_then(char_shape);
+            chars.next_if_eq(&close);
+            shape
+        };
+        self.hold = bracketed('[', ']');
+        self.current = bracketed('(', ')');
+        self.queue = chars.filter_map(char_shape).collect();
+    }
+}
+
+#[derive(Default)]
+struct Encoder {
+    values: Vec<u32>,
+}
+
+impl Encoder {
+    /// Pushes a value as a number of little endian base 64 digits
+    fn push(&mut self, value: u32, digits: u32) {
+        let mut value = value;
+        for _ in 0..digits {
+            self.values.push(value % 64);
+            value /= 64;
+        }
+    }
+
+    fn finish(self) -> String {
+        let data = self
+            .values
+            .iter()
+            .map(|v| ENCODE_TABLE[*v as usize] as char)
+            .collect::<String>();
+        let split = data.len().min(FIRST_LINE_LENGTH);
+        let (first, rest) = data.split_at(split);
+        let mut lines = vec![first.to_string()];
+        lines.extend(rest.as_bytes().chunks(LINE_LENGTH).map(|c| {
+            String::from_utf8(c.to_vec()).unwrap()
+        }));
+        format!("{}{}", VERSION_PREFIX, lines.join("?"))
+    }
+}
+
+struct Decoder {
+    values: Vec<u32>,
+    index: usize,
+}
+
+impl Decoder {
+    fn poll(&mut self, digits: usize) -> Result<u32, String> {
+        let digits = self
+            .values
+            .get(self.index..self.index + digits)
+            .ok_or("fumen is too short")?;
+        self.index += digits.len();
+        Ok(digits.iter().rev().fold(0, |value, digit| value * 64 + digit))
+    }
+}
+
+/// JavaScript's escape(), as fumen escapes comments with it
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) => c.to_string(),
+            c if (c as u32) < 0x100 => format!("%{:02X}", c as u32),
+            c => format!("%u{:04X}", c as u32),
+        })
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(index) = rest.find('%') {
+        result.push_str(&rest[..index]);
+        rest = &rest[index + 1..];
+        let (digits, length) = match rest.strip_prefix('u') {
+            Some(unicode) => (unicode.get(..4), 5),
+            None => (rest.get(..2), 2),
+        };
+        match digits
+            .and_then(|d| u32::from_str_radix(d, 16).ok())
+            .and_then(char::from_u32)
+        {
+            Some(c) => {
+                result.push(c);
+                rest = &rest[length..];
+            }
+            None => result.push('%'),
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Encodes a single page v115 fumen
+pub fn encode(page: &FumenPage) -> String {
+    let mut encoder = Encoder::default();
+
+    // fumen orders blocks from the top down, then the garbage row which is always empty here
+    let blocks = (0..FUMEN_HEIGHT)
+        .rev()
+        .flat_map(|y| {
+            (0..BOARD_WIDTH as usize).map(move |x| {
+                page.field.get(y as usize).map_or(0, |row| block_code(row[x]))
+            })
+        })
+        .chain((0..BOARD_WIDTH).map(|_| 0))
+        .collect::<Vec<u32>>();
+
+    // run lengths of the difference from the previous page, which for the first page is empty
+    let mut runs: Vec<(u32, u32)> = vec![];
+    for block in blocks {
+        let diff = block + DIFF_OFFSET;
+        match runs.last_mut() {
+            Some((run_diff, count)) if *run_diff == diff => *count += 1,
+            _ => runs.push((diff, 1)),
+        }
+    }
+    for (diff, count) in runs.iter() {
+        encoder.push(diff * FIELD_BLOCKS + count - 1, 2);
+    }
+    if runs == [(DIFF_OFFSET, FIELD_BLOCKS)] {
+        // number of following pages that repeat the same field
+        encoder.push(0, 1);
+    }
+
+    let comment = page.queue_comment().map(|c| escape(&c));
+    let (shape, rotation, position) = match page.piece {
+        Some(piece) => (shape_code(piece.shape), rotation_code(piece.rotation), piece.position()),
+        None => (0, 0, 0),
+    };
+    let lock = true;
+    let colorize = true;
+    let flags = [!lock, comment.is_some(), colorize, false, false];
+    let action = flags.iter().fold(0, |value, flag| value * 2 + *flag as u32);
+    encoder.push(((action * FIELD_BLOCKS + position) * 4 + rotation) * 8 + shape, 3);
+
+    if let Some(comment) = comment {
+        let chars = comment.as_bytes();
+        encoder.push(chars.len() as u32, 2);
+        for chunk in chars.chunks(4) {
+            let value = chunk.iter().rev().fold(0, |value, c| {
+                let index = COMMENT_TABLE.iter().position(|t| t == c).unwrap_or(0) as u32;
+                value * COMMENT_CHAR_VALUES + index
+            });
+            encoder.push(value, 5);
+        }
+    }
+
+    encoder.finish()
+}
+
+/// Decodes the first page of a v115 fumen, which may be part of a URL
+pub fn decode(fumen: &str) -> Result<FumenPage, String> {
+    let start = fumen.find(VERSION_PREFIX).ok_or("not a v115 fumen")? + VERSION_PREFIX.len();
+    let values = fumen[start..]
+        .trim()
+        .chars()
+        .filter(|c| *c != '?')
+        .map(|c| {
+            ENCODE_TABLE
+                .iter()
+                .position(|t| *t as char == c)
+                .map(|v| v as u32)
+                .ok_or_else(|| format!("bad fumen character '{}'", c))
+        })
+        .collect::<Result<Vec<u32>, String>>()?;
+    let mut decoder = Decoder { values, index: 0 };
+
+    let mut blocks = vec![0; FIELD_BLOCKS as usize];
+    let mut index = 0;
+    while index < FIELD_BLOCKS {
+        let run = decoder.poll(2)?;
+        let diff = run / FIELD_BLOCKS;
+        let count = run % FIELD_BLOCKS + 1;
+        if diff == DIFF_OFFSET && count == FIELD_BLOCKS {
+            decoder.poll(1)?;
+        }
+        let valid_diff = (DIFF_OFFSET..=DIFF_OFFSET + GARBAGE_CODE).contains(&diff);
+        if index + count > FIELD_BLOCKS || !valid_diff {
+            return Err("bad fumen field".to_string());
+        }
+        for block in blocks[index as usize..(index + count) as usize].iter_mut() {
+            *block = diff - DIFF_OFFSET;
+        }
+        index += count;
+    }
+
+    let mut page = FumenPage {
+        field: (0..FUMEN_HEIGHT)
+            .map(|y| {
+                let top_index = (FUMEN_HEIGHT - y - 1) * BOARD_WIDTH;
+                std::array::from_fn(|x| code_block(blocks[top_index as usize + x]))
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut action = decoder.poll(3)?;
+    let shape = code_shape(action % 8);
+    action /= 8;
+    let rotation = code_rotation(action % 4);
+    action /= 4;
+    let position = action % FIELD_BLOCKS;
+    action /= FIELD_BLOCKS;
+    // flags from the lowest bit: rise, mirror, colorize, comment, not lock
+    let has_comment = (action >> 3) & 1 == 1;
+    page.piece = shape.map(|shape| FumenPiece::from_position(shape, rotation, position));
+    page.current = shape;
+
+    if has_comment {
+        let length = decoder.poll(2)? as usize;
+        let mut comment = String::new();
+        while comment.len() < length {
+            let mut value = decoder.poll(5)?;
+            for _ in 0..4 {
+                let c = COMMENT_TABLE
+                    .get((value % COMMENT_CHAR_VALUES) as usize)
+                    .ok_or("bad fumen comment")?;
+                comment.push(*c as char);
+                value /= COMMENT_CHAR_VALUES;
+            }
+        }
+        comment.truncate(length);
+        page.read_queue_comment(&unescape(&comment));
+    }
+
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::compact_destroy_lines;
+    use crate::game::rotation::RotationSystem;
+
+    /// An I ready to drop into a 4 row well with a pair of O blocks above
+    const TETRIS_READY: &str = "v115@zgRpHeI8AeI8AeI8AeI8KepgXOAFLDmClcJSAS0USA?ylAAA";
+    /// A T in place to fill a t-spin double slot, holding an O with S & Z to come
+    const T_SPIN_DOUBLE: &str =
+        "v115@HhA8AehlFeB8CeH8AeF8JelKYXAFLDmClcJSAVjiSA?VG88AYe88A5sDCA";
+
+    fn row(text: &str) -> StackRow {
+        std::array::from_fn(|x| match text.as_bytes()[x] {
+            b'#' => BlockState::Garbage,
+            b'.' => BlockState::Empty,
+            c => BlockState::Stack(char_shape(c as char).unwrap(), Rotation::North, 0),
+        })
+    }
+
+    #[test]
+    fn empty_field() {
+        assert_eq!(encode(&FumenPage::default()), "v115@vhAAgH");
+        let page = decode("v115@vhAAgH").unwrap();
+        assert_eq!(page.field.len(), FUMEN_HEIGHT as usize);
+        assert!(page.field.iter().flatten().all(|b| *b == BlockState::Empty));
+        assert_eq!(page.piece, None);
+    }
+
+    #[test]
+    fn decodes_from_url() {
+        assert!(decode("https://fumen.zui.jp/?v115@vhAAgH").is_ok());
+        assert!(decode("vhAAgH").is_err());
+        assert!(decode("v115@vh").is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let page = FumenPage {
+            field: vec![row("IIII#.JJJ."), row("LOOZT.SSJ.")],
+            piece: Some(FumenPiece {
+                shape: TetrominoShape::T,
+                rotation: Rotation::East,
+                center: Point::new(5, 10),
+            }),
+            hold: Some(TetrominoShape::O),
+            current: Some(TetrominoShape::T),
+            queue: vec![TetrominoShape::I, TetrominoShape::S, TetrominoShape::Z],
+        };
+        let decoded = decode(&encode(&page)).unwrap();
+        assert_eq!(decoded.field[..2], page.field);
+        assert!(decoded.field[2..].iter().flatten().all(|b| *b == BlockState::Empty));
+        assert_eq!(decoded.piece, page.piece);
+        assert_eq!(decoded.hold, page.hold);
+        assert_eq!(decoded.current, page.current);
+        assert_eq!(decoded.queue, page.queue);
+    }
+
+    #[test]
+    fn round_trip_every_piece() {
+        for shape in TetrominoShape::ALL {
+            for rotation in [Rotation::North, Rotation::East, Rotation::South, Rotation::West] {
+                let piece = FumenPiece {
+                    shape,
+                    rotation,
+                    center: Point::new(4, 2),
+                };
+                let page = FumenPage {
+                    piece: Some(piece),
+                    ..Default::default()
+                };
+                let decoded = decode(&encode(&page)).unwrap().piece.unwrap();
+                assert_eq!(decoded.minos(), piece.minos(), "{:?} {:?}", shape, rotation);
+            }
+        }
+    }
+
+    #[test]
+    fn splits_long_fumen() {
+        let page = FumenPage {
+            field: (0..FUMEN_HEIGHT).map(|_| row("I.L.O.Z.T.")).collect(),
+            ..Default::default()
+        };
+        let fumen = encode(&page);
+        assert!(fumen.split('?').all(|line| line.len() <= LINE_LENGTH));
+        assert_eq!(fumen.split('?').next().unwrap().len(), LINE_LENGTH);
+        assert_eq!(decode(&fumen).unwrap().field, page.field);
+    }
+
+    #[test]
+    fn escapes_comments() {
+        assert_eq!(escape("#Q=[T](I)"), "%23Q%3D%5BT%5D%28I%29");
+        assert_eq!(unescape(&escape("#Q=[T](I) é")), "#Q=[T](I) é");
+        assert_eq!(unescape("100%"), "100%");
+    }
+
+    #[test]
+    fn board_round_trip() {
+        let mut board = Board::new(RotationSystem::Srs);
+        board.preset_stack(&[row("IIIIJJJ.OO"), row("#########.")]);
+        assert!(board.try_spawn_tetromino(TetrominoShape::L).is_some());
+        board.rotate(true);
+        let page = FumenPage::from_board(&board);
+        let sorted = |mut minos: Minos| {
+            minos.sort_by_key(|p| (p.x, p.y));
+            minos
+        };
+        let tetromino = board.tetromino().unwrap();
+        assert_eq!(sorted(page.piece.unwrap().minos()), sorted(tetromino.minos()));
+
+        let imported = decode(&encode(&page)).unwrap().to_board(RotationSystem::Srs).unwrap();
+        for y in 0..FUMEN_HEIGHT {
+            let expected = page.field[y as usize];
+            let stack = imported.row(y).iter().map(|b| if b.collides() { *b } else { BlockState::Empty });
+            assert!(stack.eq(expected), "row {}", y);
+        }
+        let imported_tetromino = imported.tetromino().unwrap();
+        assert_eq!(imported_tetromino.shape(), TetrominoShape::L);
+        assert_eq!(sorted(imported_tetromino.minos()), sorted(tetromino.minos()));
+    }
+
+    #[test]
+    fn imports_every_piece_with_each_rotation_system() {
+        for rotation_system in [RotationSystem::Srs, RotationSystem::Ars, RotationSystem::Classic] {
+            for shape in TetrominoShape::ALL {
+                for rotation in [Rotation::North, Rotation::East, Rotation::South, Rotation::West] {
+                    let piece = FumenPiece {
+                        shape,
+                        rotation,
+                        center: Point::new(4, 10),
+                    };
+                    let page = FumenPage {
+                        piece: Some(piece),
+                        ..Default::default()
+                    };
+                    let board = page.to_board(rotation_system).unwrap();
+                    let mut minos = board.tetromino().unwrap().minos();
+                    minos.sort_by_key(|p| (p.x, p.y));
+                    let mut expected = piece.minos();
+                    expected.sort_by_key(|p| (p.x, p.y));
+                    assert_eq!(minos, expected, "{:?} {:?}", shape, rotation);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_a_piece_in_the_stack() {
+        let page = FumenPage {
+            field: vec![row("IIIIJJJ.OO")],
+            piece: Some(FumenPiece {
+                shape: TetrominoShape::O,
+                rotation: Rotation::North,
+                center: Point::new(0, 0),
+            }),
+            ..Default::default()
+        };
+        assert!(page.to_board(RotationSystem::Srs).is_err());
+    }
+
+    #[test]
+    fn pattern_of_imported_stack() {
+        let page = FumenPage {
+            field: vec![
+                row("IIIIIIIIIT"),
+                row("#########T"),
+                row("OO.#.####T"),
+                row("OOJJJJJJJJ"),
+            ],
+            ..Default::default()
+        };
+        let mut board = Board::new(RotationSystem::Srs);
+        board.preset_stack(&decode(&encode(&page)).unwrap().field);
+        assert_eq!(compact_destroy_lines(board.pattern()), vec![0, 1, 3]);
+        board.destroy(board.pattern());
+        assert_eq!(board.row(0), row("OO.#.####T"));
+        assert_eq!(board.row(1), [BlockState::Empty; BOARD_WIDTH as usize]);
+    }
+
+    #[test]
+    fn clears_a_tetris_from_a_known_position() {
+        let mut board = decode(TETRIS_READY).unwrap().to_board(RotationSystem::Srs).unwrap();
+        assert_eq!(board.tetromino().unwrap().shape(), TetrominoShape::I);
+        assert_eq!(board.pattern(), [None; 4]);
+
+        assert!(board.hard_drop().is_some());
+        board.lock();
+        assert_eq!(compact_destroy_lines(board.pattern()), vec![0, 1, 2, 3]);
+        board.destroy(board.pattern());
+        assert_eq!(board.row(0), row("OO........"));
+        assert!(board.row(1).iter().all(|b| *b == BlockState::Empty));
+    }
+
+    #[test]
+    fn clears_a_t_spin_double_from_a_known_position() {
+        let page = decode(T_SPIN_DOUBLE).unwrap();
+        assert_eq!(page.hold, Some(TetrominoShape::O));
+        assert_eq!(page.queue, vec![TetrominoShape::S, TetrominoShape::Z]);
+
+        let mut board = page.to_board(RotationSystem::Srs).unwrap();
+        board.lock();
+        assert_eq!(compact_destroy_lines(board.pattern()), vec![0, 1]);
+        board.destroy(board.pattern());
+        assert_eq!(board.row(0), row("#.LL......"));
+        assert!(board.row(1).iter().all(|b| *b == BlockState::Empty));
+    }
+}
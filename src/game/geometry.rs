@@ -1,7 +1,8 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, AddAssign, Neg, Sub};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -77,7 +78,7 @@ impl AddAssign for Point {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Rotation {
     North,
     East,
@@ -117,6 +118,10 @@ impl Rotation {
             }
         }
     }
+    pub fn flip(&self) -> Rotation {
+        self.rotate(true).rotate(true)
+    }
+
     pub fn angle(&self) -> f64 {
         // match self {
         //     Rotation::North => 0.0,
@@ -148,4 +153,10 @@ mod tests {
         assert_eq!(Rotation::North.rotate(false), Rotation::West);
         assert_eq!(Rotation::East.rotate(false), Rotation::North);
     }
+
+    #[test]
+    fn flip_rotation() {
+        assert_eq!(Rotation::North.flip(), Rotation::South);
+        assert_eq!(Rotation::West.flip(), Rotation::East);
+    }
 }
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub const MASTER_MAX_LEVEL: u32 = 999;
+const SECTION_LEVELS: u32 = 100;
+
+/// Level & section time tracking for master mode.
+/// Each spawn and each cleared line is worth a level, but only a line clear can finish a section.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasterProgress {
+    section_times: Vec<Duration>,
+    section_time: Duration,
+}
+
+impl MasterProgress {
+    pub fn section_times(&self) -> &[Duration] {
+        &self.section_times
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.section_times.iter().sum::<Duration>() + self.section_time
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.section_time += delta;
+    }
+
+    /// Level after spawning a tetromino, which stops at the end of each section
+    pub fn spawn_level(&self, level: u32) -> u32 {
+        if level % SECTION_LEVELS == SECTION_LEVELS - 1 || level >= MASTER_MAX_LEVEL - 1 {
+            level
+        } else {
+            level + 1
+        }
+    }
+
+    /// Level after clearing lines, records the time of any completed section
+    pub fn clear_level(&mut self, level: u32, lines: u32) -> u32 {
+        let next_level = (level + lines).min(MASTER_MAX_LEVEL);
+        if next_level / SECTION_LEVELS > level / SECTION_LEVELS || next_level == MASTER_MAX_LEVEL {
+            self.section_times.push(self.section_time);
+            self.section_time = Duration::ZERO;
+        }
+        next_level
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_stops_at_section_end() {
+        let progress = MasterProgress::default();
+        assert_eq!(progress.spawn_level(0), 1);
+        assert_eq!(progress.spawn_level(98), 99);
+        assert_eq!(progress.spawn_level(99), 99);
+        assert_eq!(progress.spawn_level(998), 998);
+    }
+
+    #[test]
+    fn clearing_into_next_section_records_time() {
+        let mut progress = MasterProgress::default();
+        progress.update(Duration::from_secs(60));
+        assert_eq!(progress.clear_level(97, 2), 99);
+        assert!(progress.section_times().is_empty());
+        assert_eq!(progress.clear_level(99, 4), 103);
+        assert_eq!(progress.section_times(), [Duration::from_secs(60)]);
+        progress.update(Duration::from_secs(5));
+        assert_eq!(progress.total_time(), Duration::from_secs(65));
+    }
+
+    #[test]
+    fn clearing_to_max_level_records_final_section() {
+        let mut progress = MasterProgress::default();
+        progress.update(Duration::from_secs(30));
+        assert_eq!(progress.clear_level(998, 4), MASTER_MAX_LEVEL);
+        assert_eq!(progress.section_times(), [Duration::from_secs(30)]);
+    }
+}
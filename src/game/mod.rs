@@ -1,28 +1,38 @@
 use crate::event::{GameEvent, GameOverCondition};
 use crate::game::block::BlockState;
-use crate::game::board::DestroyLines;
-use crate::game::random::{RandomTetromino, PEEK_SIZE};
+use crate::game::board::{compact_destroy_lines, DestroyLines, StackRow};
+use crate::game::fumen::FumenPage;
+use crate::game::master::MasterProgress;
+use crate::game::puzzle::PuzzleGoal;
+use crate::game::random::{RandomTetromino, Seed, PEEK_SIZE};
+use crate::game::rotation::RotationSystem;
+use crate::game::timing::GameTimings;
 use board::Board;
+use serde::{Deserialize, Serialize};
 
 use std::cmp::{max, min};
+use std::collections::VecDeque;
 
 use std::time::Duration;
 use tetromino::TetrominoShape;
 
 pub mod block;
 pub mod board;
+pub mod fumen;
 pub mod geometry;
+pub mod master;
+pub mod puzzle;
 pub mod random;
+pub mod rotation;
+pub mod survival;
 pub mod tetromino;
+pub mod timing;
 
 const LINES_PER_LEVEL: u32 = 10;
+const MAX_UNDO_POINTS: usize = 100;
 const SOFT_DROP_STEP_FACTOR: u32 = 20;
 const SOFT_DROP_SPAWN_FACTOR: u32 = 10;
-const MIN_SPAWN_DELAY: Duration = Duration::from_millis(500);
-const LOCK_DURATION: Duration = Duration::from_millis(500);
-const SOFT_DROP_LOCK_DURATION: Duration = Duration::from_millis(500 / 2);
-const MAX_LOCK_PLACEMENTS: u32 = 15;
-const GARBAGE_WAIT: Duration = Duration::from_millis(50);
+const MIN_SOFT_DROP_STEP: Duration = Duration::from_millis(7);
 
 const SINGLE_POINTS: u32 = 100;
 const DOUBLE_POINTS: u32 = 300;
@@ -33,32 +43,14 @@ const DIFFICULT_MULTIPLIER: f64 = 1.5;
 const SOFT_DROP_POINTS_PER_ROW: u32 = 1;
 const HARD_DROP_POINTS_PER_ROW: u32 = 2;
 
-// pre-calculated step durations in ms: 1000 * (0.8 - (level as f64 * 0.007)).powi(level as i32)
-// doing it like this as hashmaps cannot be constant and fp logic is not yet supported at compile time
-const STEP_0: Duration = Duration::from_millis(1000);
-const STEP_1: Duration = Duration::from_millis(793);
-const STEP_2: Duration = Duration::from_millis(618);
-const STEP_3: Duration = Duration::from_millis(473);
-const STEP_4: Duration = Duration::from_millis(355);
-const STEP_5: Duration = Duration::from_millis(262);
-const STEP_6: Duration = Duration::from_millis(190);
-const STEP_7: Duration = Duration::from_millis(135);
-const STEP_8: Duration = Duration::from_millis(94);
-const STEP_9: Duration = Duration::from_millis(64);
-const STEP_10: Duration = Duration::from_millis(43);
-const STEP_11: Duration = Duration::from_millis(28);
-const STEP_12: Duration = Duration::from_millis(18);
-const STEP_13: Duration = Duration::from_millis(11);
-const STEP_14: Duration = Duration::from_millis(7);
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GameState {
     Spawn(Duration, TetrominoShape),
     Fall(Duration),
     Lock(Duration),
     HardDropLock,
-    Pattern,               // check the board for patterns to destroy e.g. lines
-    Destroy(DestroyLines), // destroy marked patterns
+    Pattern,                         // check the board for patterns to destroy e.g. lines
+    Destroy(Duration, DestroyLines), // destroy marked patterns
     GameOver,
     SpawnGarbage {
         duration: Duration,
@@ -67,18 +59,63 @@ pub enum GameState {
     },
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Combo {
     count: u32,
     difficult: bool,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 struct HoldState {
     shape: TetrominoShape,
     locked: bool,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    Standard,
+    /// Levels count to 999 by pieces & lines rather than by lines alone
+    Master,
+    /// Topping out clears the board, the level never increases and pieces can be undone.
+    /// Without gravity tetrominos only fall when dropped
+    Zen {
+        gravity: bool,
+    },
+    /// A preset stack is solved with a fixed sequence of tetrominos, the level never increases
+    Puzzle {
+        goal: PuzzleGoal,
+    },
+}
+
+/// Snapshot taken as a tetromino spawns so that it can be undone in zen mode
+#[derive(Clone, Serialize, Deserialize)]
+struct UndoPoint {
+    board: Board,
+    random: RandomTetromino,
+    shape: TetrominoShape,
+    hold: Option<HoldState>,
+    lines: u32,
+    score: u32,
+    pieces: u32,
+    combo: Option<Combo>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum InitialRotation {
+    Clockwise,
+    Anticlockwise,
+    HalfTurn,
+}
+
+/// Inputs received while there is no tetromino in play, applied as the next tetromino spawns
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct InitialActions {
+    rotation: Option<InitialRotation>, // IRS
+    hold: bool,                        // IHS
+    shift: i32,                        // buffered left (negative) & right (positive) moves
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Game {
     player: u32,
     board: Board,
@@ -86,12 +123,22 @@ pub struct Game {
     level: u32,
     lines: u32,
     score: u32,
+    pieces: u32,
+    time: Duration,
     combo: Option<Combo>,
     state: GameState,
     soft_drop: bool,
     skip_next_spawn_delay: bool,
     hold: Option<HoldState>,
-    garbage_buffer: u32,
+    garbage_buffer: VecDeque<u32>, // holes of each line of garbage waiting to be spawned
+    buffer_inputs: bool,
+    initial_actions: InitialActions,
+    timings: GameTimings,
+    mode: GameMode,
+    master: Option<MasterProgress>,
+    undo_points: VecDeque<UndoPoint>,
+    t_spin: bool, // the last tetromino to lock was a t-spin
+    puzzle_solved: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -100,27 +147,47 @@ pub struct GameMetrics {
     pub level: u32,
     pub lines: u32,
     pub score: u32,
+    pub pieces: u32,
+    pub time: Duration,
     pub combo: Option<Combo>,
-    pub queue: [TetrominoShape; PEEK_SIZE],
+    pub queue: [Option<TetrominoShape>; PEEK_SIZE],
     pub hold: Option<TetrominoShape>,
 }
 
 impl Game {
-    pub fn new(player: u32, level: u32, mut random: RandomTetromino) -> Game {
+    pub fn new(
+        player: u32,
+        level: u32,
+        mut random: RandomTetromino,
+        rotation_system: RotationSystem,
+        buffer_inputs: bool,
+        timings: GameTimings,
+        mode: GameMode,
+    ) -> Game {
         let first_shape = random.next();
         Game {
             player,
-            board: Board::new(),
+            board: Board::new(rotation_system),
             random,
             level,
             lines: 0,
             score: 0,
+            pieces: 0,
+            time: Duration::ZERO,
             combo: None,
             state: GameState::Spawn(Duration::ZERO, first_shape),
             soft_drop: false,
             skip_next_spawn_delay: false,
             hold: None,
-            garbage_buffer: 0,
+            garbage_buffer: VecDeque::new(),
+            buffer_inputs,
+            initial_actions: InitialActions::default(),
+            timings,
+            mode,
+            master: (mode == GameMode::Master).then(MasterProgress::default),
+            undo_points: VecDeque::new(),
+            t_spin: false,
+            puzzle_solved: false,
         }
     }
 
@@ -128,10 +195,38 @@ impl Game {
         self.level
     }
 
+    pub fn master(&self) -> Option<&MasterProgress> {
+        self.master.as_ref()
+    }
+
+    pub fn is_puzzle_solved(&self) -> bool {
+        self.puzzle_solved
+    }
+
+    /// Inputs are buffered for the next tetromino while waiting for it to spawn
+    pub fn is_buffering_inputs(&self) -> bool {
+        self.buffer_inputs
+            && matches!(
+                self.state,
+                GameState::Spawn(_, _)
+                    | GameState::HardDropLock
+                    | GameState::Pattern
+                    | GameState::Destroy(_, _)
+                    | GameState::SpawnGarbage { .. }
+            )
+    }
+
     pub fn hold(&mut self) -> Option<GameEvent> {
+        if self.is_buffering_inputs() {
+            self.initial_actions.hold = true;
+            return None;
+        }
+
+        let lock_delay = self.lock_delay();
         if !(matches!(self.state, GameState::Fall(_))
-            || matches!(self.state, GameState::Lock(duration) if duration < LOCK_DURATION))
+            || matches!(self.state, GameState::Lock(duration) if duration < lock_delay))
             || matches!(self.hold, Some(HoldState { locked: true, .. }))
+            || (self.hold.is_none() && self.random.is_exhausted())
         {
             // hold is blocked
             return None;
@@ -147,7 +242,7 @@ impl Game {
             Some(HoldState { shape, .. }) => shape,
         };
 
-        self.state = GameState::Spawn(MIN_SPAWN_DELAY, next_shape);
+        self.state = GameState::Spawn(self.timings.spawn_delay(self.level), next_shape);
         self.hold = Some(HoldState {
             locked: true,
             shape: held_shape,
@@ -155,6 +250,38 @@ impl Game {
         Some(GameEvent::Hold)
     }
 
+    /// Returns the board to how it was before the last tetromino was placed
+    pub fn undo(&mut self) -> Option<GameEvent> {
+        if !matches!(self.mode, GameMode::Zen { .. }) {
+            return None;
+        }
+
+        let current_points = match self.state {
+            // the tetromino in play has its own undo point
+            GameState::Fall(_) | GameState::Lock(_) => 1,
+            GameState::Spawn(_, _) => 0,
+            _ => return None,
+        };
+        if self.undo_points.len() <= current_points {
+            return None;
+        }
+        self.undo_points
+            .truncate(self.undo_points.len() - current_points);
+
+        let point = self.undo_points.pop_back().unwrap();
+        self.board = point.board;
+        self.random = point.random;
+        self.hold = point.hold;
+        self.lines = point.lines;
+        self.score = point.score;
+        self.pieces = point.pieces;
+        self.combo = point.combo;
+        self.initial_actions = InitialActions::default();
+        self.skip_next_spawn_delay = true;
+        self.state = GameState::Spawn(Duration::ZERO, point.shape);
+        Some(GameEvent::Undo)
+    }
+
     pub fn set_soft_drop(&mut self, soft_drop: bool) -> Option<GameEvent> {
         self.soft_drop = soft_drop;
         if soft_drop {
@@ -183,13 +310,69 @@ impl Game {
             level: self.level,
             lines: self.lines,
             score: self.score,
+            pieces: self.pieces,
+            time: self.time,
             combo: self.combo,
             queue: self.random.peek(),
             hold: self.hold.map(|h| h.shape),
         }
     }
 
+    /// The board, hold & queue as a fumen string
+    pub fn fumen(&self) -> String {
+        let mut page = FumenPage::from_board(&self.board);
+        page.hold = self.hold.map(|h| h.shape);
+        page.current = match self.state {
+            GameState::Spawn(_, shape)
+            | GameState::SpawnGarbage {
+                next_shape: shape, ..
+            } => Some(shape),
+            _ => page.current,
+        };
+        page.queue = self.random.peek().into_iter().flatten().collect();
+        fumen::encode(&page)
+    }
+
+    /// Replaces the board, hold & queue with those of a fumen string
+    pub fn import_fumen(&mut self, fumen: &str) -> Result<(), String> {
+        let page = fumen::decode(fumen)?;
+        let board = page.to_board(self.board.rotation_system())?;
+        let mut random = self.random.clone();
+        random.preset_queue(&page.queue);
+        if board.tetromino().is_none()
+            && page.current.is_none()
+            && page.hold.is_none()
+            && random.is_exhausted()
+        {
+            // a fixed sequence e.g. a puzzle has no randomizer to deal from
+            return Err("no tetromino to play".to_string());
+        }
+
+        self.board = board;
+        self.random = random;
+        self.hold = page.hold.map(|shape| HoldState {
+            shape,
+            locked: false,
+        });
+        self.state = match (self.board.tetromino(), page.current) {
+            (Some(_), _) => GameState::Fall(Duration::ZERO),
+            (None, Some(shape)) => GameState::Spawn(Duration::ZERO, shape),
+            (None, None) => {
+                let shape = self.next_shape().expect("checked for a tetromino to play");
+                GameState::Spawn(Duration::ZERO, shape)
+            }
+        };
+        self.garbage_buffer.clear();
+        self.initial_actions = InitialActions::default();
+        self.undo_points.clear();
+        Ok(())
+    }
+
     pub fn left(&mut self) -> Option<GameEvent> {
+        if self.is_buffering_inputs() {
+            self.initial_actions.shift -= 1;
+            return None;
+        }
         if self.with_checking_lock(|board| board.left()) {
             Some(GameEvent::Move)
         } else {
@@ -198,6 +381,10 @@ impl Game {
     }
 
     pub fn right(&mut self) -> Option<GameEvent> {
+        if self.is_buffering_inputs() {
+            self.initial_actions.shift += 1;
+            return None;
+        }
         if self.with_checking_lock(|board| board.right()) {
             Some(GameEvent::Move)
         } else {
@@ -206,6 +393,14 @@ impl Game {
     }
 
     pub fn rotate(&mut self, clockwise: bool) -> Option<GameEvent> {
+        if self.is_buffering_inputs() {
+            self.initial_actions.rotation = Some(if clockwise {
+                InitialRotation::Clockwise
+            } else {
+                InitialRotation::Anticlockwise
+            });
+            return None;
+        }
         if self.with_checking_lock(|board| board.rotate(clockwise)) {
             Some(GameEvent::Rotate)
         } else {
@@ -213,8 +408,45 @@ impl Game {
         }
     }
 
+    pub fn half_turn(&mut self) -> Option<GameEvent> {
+        if self.is_buffering_inputs() {
+            self.initial_actions.rotation = Some(InitialRotation::HalfTurn);
+            return None;
+        }
+        if self.with_checking_lock(|board| board.half_turn()) {
+            Some(GameEvent::Rotate)
+        } else {
+            None
+        }
+    }
+
+    /// Pre-fill the board with messy garbage to dig through
+    pub fn fill_garbage(&mut self, rows: u32) {
+        for hole in self.random.next_dig_holes(rows) {
+            self.board.send_garbage(hole);
+        }
+    }
+
+    /// Sets up the bottom of the board, listed from the bottom up
+    pub fn preset_stack(&mut self, rows: &[StackRow]) {
+        self.board.preset_stack(rows);
+    }
+
+    pub fn garbage_rows(&self) -> u32 {
+        self.board.garbage_rows()
+    }
+
+    pub fn stack_height(&self) -> u32 {
+        self.board.stack_height()
+    }
+
+    pub fn seed(&self) -> Seed {
+        self.random.seed()
+    }
+
     pub fn send_garbage(&mut self, rows: u32) {
-        self.garbage_buffer += rows;
+        let holes = self.random.next_garbage_attack(rows);
+        self.garbage_buffer.extend(holes);
     }
 
     fn with_checking_lock<F>(&mut self, mut f: F) -> bool
@@ -223,26 +455,32 @@ impl Game {
     {
         match self.state {
             GameState::Lock(lock_duration) => {
+                let lock_delay = self.lock_delay();
+                let max_lock_placements = self.timings.max_lock_placements;
                 // 1. check if the lock is already breached (we send movements before a lock update)
-                if lock_duration > LOCK_DURATION {
+                if lock_duration > lock_delay {
                     return false;
                 }
-                // 2. check if this tetromino used all it's lock movements for this altitude
-                if self.board.lock_placements() >= MAX_LOCK_PLACEMENTS {
+                // 2. movement under lock never resets it, the tetromino must fall to reset it
+                if max_lock_placements == 0 {
+                    return f(&mut self.board);
+                }
+                // 3. check if this tetromino used all it's lock movements for this altitude
+                if self.board.lock_placements() >= max_lock_placements {
                     // the tetromino has already run out of lock movements, lock it asap
-                    self.state = GameState::Lock(LOCK_DURATION);
+                    self.state = GameState::Lock(lock_delay);
                     return false;
                 }
-                // 3. check the movement was blocked by the board
+                // 4. check the movement was blocked by the board
                 if !f(&mut self.board) {
                     return false;
                 }
-                if self.board.register_lock_placement() < MAX_LOCK_PLACEMENTS {
+                if self.board.register_lock_placement() < max_lock_placements {
                     // movement is allowed under lock, lock is reset
                     self.state = GameState::Fall(Duration::ZERO);
                 } else {
                     // the tetromino just ran out of lock movements, lock it asap
-                    self.state = GameState::Lock(LOCK_DURATION);
+                    self.state = GameState::Lock(lock_delay);
                 }
                 true
             }
@@ -251,13 +489,20 @@ impl Game {
     }
 
     pub fn update(&mut self, delta: Duration) -> Option<GameEvent> {
+        if self.state != GameState::GameOver {
+            self.time += delta;
+            if let Some(master) = self.master.as_mut() {
+                master.update(delta);
+            }
+        }
+
         let (state, event) = match self.state {
             GameState::Spawn(duration, shape) => self.spawn(duration + delta, shape),
             GameState::Fall(duration) => self.fall(duration + delta),
             GameState::Lock(duration) => self.lock(duration + delta, false),
-            GameState::HardDropLock => self.lock(LOCK_DURATION, true),
+            GameState::HardDropLock => self.lock(self.lock_delay(), true),
             GameState::Pattern => self.pattern(),
-            GameState::Destroy(pattern) => self.destroy(pattern),
+            GameState::Destroy(duration, pattern) => self.destroy(duration + delta, pattern),
             GameState::SpawnGarbage {
                 duration,
                 next_shape,
@@ -274,7 +519,7 @@ impl Game {
         duration: Duration,
         shape: TetrominoShape,
     ) -> (GameState, Option<GameEvent>) {
-        if self.garbage_buffer > 0 {
+        if !self.garbage_buffer.is_empty() {
             return (
                 GameState::SpawnGarbage {
                     duration: Duration::ZERO,
@@ -283,7 +528,7 @@ impl Game {
                 },
                 Some(GameEvent::ReceivedGarbage {
                     player: self.player,
-                    lines: self.garbage_buffer,
+                    lines: self.garbage_buffer.len() as u32,
                 }),
             );
         }
@@ -293,7 +538,41 @@ impl Game {
         }
 
         self.skip_next_spawn_delay = false;
+        let initial_actions = std::mem::take(&mut self.initial_actions);
+        let shape = if initial_actions.hold {
+            self.initial_hold(shape)
+        } else {
+            shape
+        };
+
+        if matches!(self.mode, GameMode::Zen { .. }) {
+            self.push_undo_point(shape);
+        }
+
         if let Some(minos) = self.board.try_spawn_tetromino(shape) {
+            self.pieces += 1;
+            if let Some(master) = self.master.as_ref() {
+                self.level = master.spawn_level(self.level);
+            }
+
+            // a blocked initial rotation or shift is simply dropped
+            match initial_actions.rotation {
+                Some(InitialRotation::Clockwise) => self.board.rotate(true),
+                Some(InitialRotation::Anticlockwise) => self.board.rotate(false),
+                Some(InitialRotation::HalfTurn) => self.board.half_turn(),
+                None => false,
+            };
+            for _ in 0..initial_actions.shift.abs() {
+                let moved = if initial_actions.shift < 0 {
+                    self.board.left()
+                } else {
+                    self.board.right()
+                };
+                if !moved {
+                    break;
+                }
+            }
+
             (
                 GameState::Fall(Duration::ZERO),
                 Some(GameEvent::Spawn {
@@ -303,39 +582,104 @@ impl Game {
             )
         } else {
             // cannot spawn a tetromino is a game over event
-            (
+            self.top_out(GameOverCondition::BlockOut, Some(shape))
+        }
+    }
+
+    /// Swaps the spawning shape into hold, returning the shape to spawn instead
+    fn initial_hold(&mut self, shape: TetrominoShape) -> TetrominoShape {
+        let next_shape = match self.hold {
+            Some(HoldState { locked: true, .. }) => return shape,
+            None if self.random.is_exhausted() => return shape,
+            None => self.random.next(),
+            Some(HoldState { shape, .. }) => shape,
+        };
+        self.hold = Some(HoldState {
+            locked: true,
+            shape,
+        });
+        next_shape
+    }
+
+    fn push_undo_point(&mut self, shape: TetrominoShape) {
+        if self.undo_points.len() >= MAX_UNDO_POINTS {
+            self.undo_points.pop_front();
+        }
+        self.undo_points.push_back(UndoPoint {
+            board: self.board.clone(),
+            random: self.random.clone(),
+            shape,
+            hold: self.hold,
+            lines: self.lines,
+            score: self.score,
+            pieces: self.pieces,
+            combo: self.combo,
+        });
+    }
+
+    fn top_out(
+        &mut self,
+        condition: GameOverCondition,
+        next_shape: Option<TetrominoShape>,
+    ) -> (GameState, Option<GameEvent>) {
+        if !matches!(self.mode, GameMode::Zen { .. }) {
+            return (
                 GameState::GameOver,
                 Some(GameEvent::GameOver {
                     player: self.player,
-                    condition: GameOverCondition::BlockOut,
+                    condition,
                 }),
-            )
+            );
         }
+
+        // zen mode never ends, carry on with an empty board
+        self.board.clear();
+        self.garbage_buffer.clear();
+        self.undo_points.clear();
+        let shape = next_shape.unwrap_or_else(|| self.random.next());
+        (GameState::Spawn(Duration::ZERO, shape), None)
     }
 
     fn fall(&mut self, duration: Duration) -> (GameState, Option<GameEvent>) {
-        if duration < self.step_delay() {
+        if self.mode == (GameMode::Zen { gravity: false }) && !self.soft_drop {
+            // no gravity, only falls when dropped
+            return (GameState::Fall(duration), None);
+        }
+
+        let step_delay = self.step_delay();
+        if duration < step_delay {
             return (GameState::Fall(duration), None);
         }
 
-        if !self.board.step_down() {
+        // 20G falls straight onto the stack, otherwise step down one row
+        let mut stepped_rows = 0;
+        while self.board.step_down() {
+            stepped_rows += 1;
+            if !step_delay.is_zero() {
+                break;
+            }
+        }
+
+        if stepped_rows == 0 {
             // cannot step down, start lock
             return (GameState::Lock(Duration::ZERO), None);
         }
 
-        // has stepped down one row, update score if soft dropping
+        // has stepped down, update score if soft dropping
         if self.soft_drop {
-            self.score += SOFT_DROP_POINTS_PER_ROW;
+            self.score += stepped_rows * SOFT_DROP_POINTS_PER_ROW;
         }
 
         if self.board.is_collision() {
             // step has caused a collision, start a lock
-            let state = if self.board.lock_placements() >= MAX_LOCK_PLACEMENTS {
-                // lock asap
-                GameState::Lock(LOCK_DURATION)
-            } else {
-                GameState::Lock(Duration::ZERO)
-            };
+            let max_lock_placements = self.timings.max_lock_placements;
+            let state =
+                if max_lock_placements > 0 && self.board.lock_placements() >= max_lock_placements {
+                    // lock asap
+                    GameState::Lock(self.lock_delay())
+                } else {
+                    GameState::Lock(Duration::ZERO)
+                };
             (state, Some(GameEvent::Fall))
         } else {
             // no collisions, start a new fall step
@@ -345,9 +689,9 @@ impl Game {
 
     fn lock(&mut self, duration: Duration, hard_dropped: bool) -> (GameState, Option<GameEvent>) {
         let max_lock_duration = if self.soft_drop {
-            SOFT_DROP_LOCK_DURATION
+            self.lock_delay() / 2
         } else {
-            LOCK_DURATION
+            self.lock_delay()
         };
         if !hard_dropped && duration < max_lock_duration {
             (GameState::Lock(duration), None)
@@ -355,6 +699,7 @@ impl Game {
             // lock timeout and still colliding so lock the piece now
             // but before locking, need to check for a game over event.
             let is_lock_out = self.board.is_tetromino_above_skyline();
+            self.t_spin = self.board.is_t_spin();
 
             let minos = self.board.lock();
             // maybe unlock hold
@@ -369,13 +714,7 @@ impl Game {
             }
 
             if is_lock_out {
-                (
-                    GameState::GameOver,
-                    Some(GameEvent::GameOver {
-                        player: self.player,
-                        condition: GameOverCondition::LockOut,
-                    }),
-                )
+                self.top_out(GameOverCondition::LockOut, None)
             } else {
                 (
                     GameState::Pattern,
@@ -395,15 +734,57 @@ impl Game {
     fn pattern(&mut self) -> (GameState, Option<GameEvent>) {
         // TODO t-spin garbage
         let lines = self.board.pattern();
-        (GameState::Destroy(lines), Some(GameEvent::Destroy(lines)))
+        (
+            GameState::Destroy(Duration::ZERO, lines),
+            Some(GameEvent::Destroy(lines)),
+        )
     }
 
-    fn destroy(&mut self, lines: DestroyLines) -> (GameState, Option<GameEvent>) {
+    fn destroy(
+        &mut self,
+        duration: Duration,
+        lines: DestroyLines,
+    ) -> (GameState, Option<GameEvent>) {
+        if lines[0].is_some() && duration < self.timings.line_clear_delay(self.level) {
+            return (GameState::Destroy(duration, lines), None);
+        }
+
         self.board.destroy(lines);
-        (
-            GameState::Spawn(Duration::ZERO, self.random.next()),
-            self.update_score_and_get_garbage_to_send(lines),
-        )
+        let event = self.update_score_and_get_garbage_to_send(lines);
+        if let GameMode::Puzzle { goal } = self.mode {
+            self.puzzle_solved |= self.is_puzzle_goal_met(goal, lines);
+        }
+
+        match self.next_shape() {
+            Some(shape) => (GameState::Spawn(Duration::ZERO, shape), event),
+            // a solved puzzle is left for the match to end as a victory
+            None if self.puzzle_solved => (GameState::GameOver, event),
+            None => (
+                GameState::GameOver,
+                Some(GameEvent::GameOver {
+                    player: self.player,
+                    condition: GameOverCondition::OutOfPieces,
+                }),
+            ),
+        }
+    }
+
+    /// The next shape to spawn, once a fixed sequence runs out the held shape is played
+    fn next_shape(&mut self) -> Option<TetrominoShape> {
+        if self.random.is_exhausted() {
+            self.hold.take().map(|hold| hold.shape)
+        } else {
+            Some(self.random.next())
+        }
+    }
+
+    fn is_puzzle_goal_met(&self, goal: PuzzleGoal, lines: DestroyLines) -> bool {
+        let line_count = compact_destroy_lines(lines).len();
+        match goal {
+            PuzzleGoal::PerfectClear => line_count > 0 && self.board.is_stack_empty(),
+            PuzzleGoal::TSpinDouble => self.t_spin && line_count == 2,
+            PuzzleGoal::ClearLines { lines } => self.lines >= lines,
+        }
     }
 
     fn spawn_garbage(
@@ -412,7 +793,7 @@ impl Game {
         next_shape: TetrominoShape,
         spawned: u32,
     ) -> (GameState, Option<GameEvent>) {
-        if duration < GARBAGE_WAIT {
+        if duration < self.timings.garbage_delay {
             return (
                 GameState::SpawnGarbage {
                     duration,
@@ -423,27 +804,19 @@ impl Game {
             );
         }
 
-        let hole = self.random.next_garbage_hole();
+        let hole = self.garbage_buffer.pop_front().unwrap();
         self.board.send_garbage(hole);
 
         if self.board.is_stack_above_skyline() {
-            // TopOut
-            return (
-                GameState::GameOver,
-                Some(GameEvent::GameOver {
-                    player: self.player,
-                    condition: GameOverCondition::TopOut,
-                }),
-            );
+            return self.top_out(GameOverCondition::TopOut, Some(next_shape));
         }
 
-        self.garbage_buffer -= 1;
         let event = GameEvent::ReceivedGarbageLine {
             player: self.player,
             line: spawned,
         };
 
-        if self.garbage_buffer == 0 {
+        if self.garbage_buffer.is_empty() {
             self.skip_next_spawn_delay = true;
             (GameState::Spawn(Duration::ZERO, next_shape), Some(event))
         } else {
@@ -490,7 +863,11 @@ impl Game {
         };
 
         // calculate score delta
-        let level_multiplier = self.level + 1;
+        let level_multiplier = match self.master {
+            // master levels count up to 999 so score by section instead
+            Some(_) => self.level / 100 + 1,
+            None => self.level + 1,
+        };
         let (difficult_score_multiplier, difficult_garbage_lines) = match self.combo {
             // back to back difficult clears get a 1.5x multiplier
             Some(Combo { count, difficult }) if count > 0 && difficult => (DIFFICULT_MULTIPLIER, 1),
@@ -509,11 +886,21 @@ impl Game {
 
         // update level
         self.lines += line_count;
-        let line_level = self.lines / LINES_PER_LEVEL;
-        let level_up = line_level > self.level;
-        if level_up {
-            self.level = line_level;
-        }
+        let level_up = if let Some(master) = self.master.as_mut() {
+            let master_level = master.clear_level(self.level, line_count);
+            let section_up = master_level / 100 > self.level / 100;
+            self.level = master_level;
+            section_up
+        } else if matches!(self.mode, GameMode::Zen { .. } | GameMode::Puzzle { .. }) {
+            false
+        } else {
+            let line_level = self.lines / LINES_PER_LEVEL;
+            let level_up = line_level > self.level;
+            if level_up {
+                self.level = line_level;
+            }
+            level_up
+        };
 
         Some(GameEvent::Destroyed {
             player: self.player,
@@ -528,7 +915,16 @@ impl Game {
     }
 
     fn spawn_delay(&self) -> Duration {
-        min(self.base_delay(SOFT_DROP_SPAWN_FACTOR), MIN_SPAWN_DELAY)
+        let spawn_delay = self.timings.spawn_delay(self.level);
+        if self.timings.spawn_delay_follows_gravity {
+            min(self.base_delay(SOFT_DROP_SPAWN_FACTOR), spawn_delay)
+        } else {
+            spawn_delay
+        }
+    }
+
+    fn lock_delay(&self) -> Duration {
+        self.timings.lock_delay(self.level)
     }
 
     fn step_delay(&self) -> Duration {
@@ -536,28 +932,270 @@ impl Game {
     }
 
     fn base_delay(&self, soft_drop_factor: u32) -> Duration {
-        let base = match self.level {
-            0 => STEP_0,
-            1 => STEP_1,
-            2 => STEP_2,
-            3 => STEP_3,
-            4 => STEP_4,
-            5 => STEP_5,
-            6 => STEP_6,
-            7 => STEP_7,
-            8 => STEP_8,
-            9 => STEP_9,
-            10 => STEP_10,
-            11 => STEP_11,
-            12 => STEP_12,
-            13 => STEP_13,
-            _ => STEP_14,
-        };
-
+        let base = self.timings.gravity(self.level);
         if self.soft_drop {
-            max(base / soft_drop_factor, STEP_14)
+            min(max(base / soft_drop_factor, MIN_SOFT_DROP_STEP), base)
         } else {
             base
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::block::BlockState;
+    use crate::game::board::{BOARD_HEIGHT, BOARD_WIDTH};
+    use crate::game::timing::TimingPreset;
+    use tetromino::Tetromino;
+    use tetromino::TetrominoShape::{I, O, T};
+
+    const LONG_ENOUGH: Duration = Duration::from_secs(5);
+
+    fn game(mode: GameMode, shapes: &[TetrominoShape]) -> Game {
+        Game::new(
+            1,
+            1,
+            RandomTetromino::from_sequence(shapes),
+            RotationSystem::Srs,
+            true,
+            TimingPreset::Guideline.timings(),
+            mode,
+        )
+    }
+
+    /// Updates until the next tetromino is in play, returning the events on the way
+    fn update_until_fall(game: &mut Game) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for _ in 0..100 {
+            if matches!(game.state, GameState::Fall(_)) {
+                return events;
+            }
+            events.extend(game.update(LONG_ENOUGH));
+        }
+        panic!("stuck in {:?}", game.state)
+    }
+
+    /// Where a tetromino ends up after the same actions on an empty board
+    fn expected_tetromino(shape: TetrominoShape, actions: fn(&mut Board)) -> Tetromino {
+        let mut board = Board::new(RotationSystem::Srs);
+        board.try_spawn_tetromino(shape);
+        actions(&mut board);
+        board.tetromino().unwrap()
+    }
+
+    fn garbage_row(hole: usize) -> StackRow {
+        std::array::from_fn(|x| {
+            if x == hole {
+                BlockState::Empty
+            } else {
+                BlockState::Garbage
+            }
+        })
+    }
+
+    #[test]
+    fn round_trips_through_a_fumen() {
+        let mut exported = game(GameMode::Standard, &[O, T, I, O, T, I]);
+        update_until_fall(&mut exported);
+        exported.hold();
+        update_until_fall(&mut exported);
+        exported.left();
+
+        let mut imported = game(GameMode::Standard, &[I, I]);
+        imported.import_fumen(&exported.fumen()).unwrap();
+        assert_eq!(imported.state, GameState::Fall(Duration::ZERO));
+        assert_eq!(imported.metrics().hold, Some(O));
+        assert_eq!(imported.metrics().queue, exported.metrics().queue);
+        let tetromino = imported.board.tetromino().unwrap();
+        assert_eq!(tetromino.shape(), T);
+        assert_eq!(tetromino.minos(), exported.board.tetromino().unwrap().minos());
+        assert_eq!(imported.fumen(), exported.fumen());
+
+        // the imported tetromino plays on as normal
+        imported.hard_drop();
+        update_until_fall(&mut imported);
+        assert_eq!(imported.board.tetromino().unwrap().shape(), I);
+    }
+
+    #[test]
+    fn rejects_a_bad_fumen() {
+        let mut game = game(GameMode::Standard, &[O, T]);
+        assert!(game.import_fumen("nonsense").is_err());
+        assert_eq!(game.metrics().queue[0], Some(T));
+    }
+
+    #[test]
+    fn imports_a_board_only_fumen() {
+        let mut board = Board::new(RotationSystem::Srs);
+        board.try_spawn_tetromino(O);
+        board.hard_drop();
+        board.lock();
+        let mut page = FumenPage::from_board(&board);
+        let board_only = fumen::encode(&page);
+
+        // a fixed sequence e.g. a puzzle has nothing to spawn so is left as it was
+        let mut game = game(GameMode::Standard, &[O, T, I]);
+        let before = game.fumen();
+        assert!(game.import_fumen(&board_only).is_err());
+        assert_eq!(game.fumen(), before);
+
+        // unless the fumen has a queue to spawn from
+        page.queue = vec![T, I];
+        game.import_fumen(&fumen::encode(&page)).unwrap();
+        assert_eq!(game.state, GameState::Spawn(Duration::ZERO, T));
+        assert_eq!(game.metrics().queue[0], Some(I));
+    }
+
+    #[test]
+    fn undo_restores_the_last_placement() {
+        let mut game = game(GameMode::Zen { gravity: false }, &[O, T, I]);
+        update_until_fall(&mut game);
+        game.hard_drop();
+        update_until_fall(&mut game);
+        assert_eq!(game.board.tetromino().unwrap().shape(), T);
+        assert_eq!(game.stack_height(), 2);
+
+        assert_eq!(game.undo(), Some(GameEvent::Undo));
+        assert_eq!(game.state, GameState::Spawn(Duration::ZERO, O));
+        assert_eq!(game.stack_height(), 0);
+        assert_eq!(game.metrics().pieces, 0);
+        assert_eq!(game.metrics().queue[0], Some(T));
+
+        // the undone tetromino spawns again straight away
+        game.update(Duration::ZERO);
+        assert_eq!(game.board.tetromino().unwrap().shape(), O);
+
+        // nothing left to undo
+        assert_eq!(game.undo(), None);
+    }
+
+    #[test]
+    fn undo_is_zen_only() {
+        let mut game = game(GameMode::Standard, &[O, T, I]);
+        update_until_fall(&mut game);
+        game.hard_drop();
+        update_until_fall(&mut game);
+        assert_eq!(game.undo(), None);
+        assert_eq!(game.stack_height(), 2);
+    }
+
+    #[test]
+    fn zen_clears_the_board_on_top_out() {
+        let stack = (0..BOARD_HEIGHT as usize)
+            .map(|y| garbage_row(y % BOARD_WIDTH as usize))
+            .collect::<Vec<StackRow>>();
+
+        // spawns above the full stack then locks out
+        let mut zen = game(GameMode::Zen { gravity: true }, &[O, T, I]);
+        zen.preset_stack(&stack);
+        update_until_fall(&mut zen);
+        let mut events = Vec::from_iter(zen.update(LONG_ENOUGH));
+        events.extend(update_until_fall(&mut zen));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, GameEvent::GameOver { .. })));
+        assert!(zen.board.is_stack_empty());
+        assert_eq!(zen.board.tetromino().unwrap().shape(), T);
+
+        let mut standard = game(GameMode::Standard, &[O, T, I]);
+        standard.preset_stack(&stack);
+        update_until_fall(&mut standard);
+        for _ in 0..10 {
+            standard.update(LONG_ENOUGH);
+        }
+        assert_eq!(standard.state, GameState::GameOver);
+    }
+
+    #[test]
+    fn buffers_rotation_and_shifts_while_spawning() {
+        let mut game = game(GameMode::Standard, &[T, O, I]);
+        assert!(game.is_buffering_inputs());
+        assert_eq!(game.rotate(true), None);
+        assert_eq!(game.left(), None);
+        assert_eq!(game.left(), None);
+        assert_eq!(game.right(), None);
+        assert_eq!(game.left(), None);
+        update_until_fall(&mut game);
+
+        let expected = expected_tetromino(T, |board| {
+            board.rotate(true);
+            board.left();
+            board.left();
+        });
+        assert_eq!(game.board.tetromino(), Some(expected));
+        assert!(!game.is_buffering_inputs());
+    }
+
+    #[test]
+    fn buffers_hold_while_spawning() {
+        let mut game = game(GameMode::Standard, &[T, O, I]);
+        assert_eq!(game.hold(), None);
+        assert_eq!(game.half_turn(), None);
+        update_until_fall(&mut game);
+
+        let expected = expected_tetromino(O, |board| {
+            board.half_turn();
+        });
+        assert_eq!(game.board.tetromino(), Some(expected));
+        assert_eq!(game.metrics().hold, Some(T));
+        assert_eq!(game.metrics().queue[0], Some(I));
+
+        // the initial hold counts as the hold for this tetromino
+        assert_eq!(game.hold(), None);
+        assert_eq!(game.board.tetromino().unwrap().shape(), O);
+    }
+
+    #[test]
+    fn buffers_inputs_while_clearing_lines() {
+        let o = expected_tetromino(O, |_| {});
+        let row = std::array::from_fn(|x| {
+            if o.minos().iter().any(|mino| mino.x == x as i32) {
+                BlockState::Empty
+            } else {
+                BlockState::Garbage
+            }
+        });
+        let mut game = game(GameMode::Standard, &[O, T, I]);
+        game.preset_stack(&[row, row]);
+        update_until_fall(&mut game);
+        game.hard_drop();
+        while !matches!(game.state, GameState::Destroy(_, _)) {
+            game.update(Duration::ZERO);
+        }
+
+        assert!(game.is_buffering_inputs());
+        assert_eq!(game.rotate(false), None);
+        assert_eq!(game.right(), None);
+        assert_eq!(game.hold(), None);
+        update_until_fall(&mut game);
+
+        assert!(game.board.is_stack_empty());
+        assert_eq!(game.metrics().lines, 2);
+        let expected = expected_tetromino(I, |board| {
+            board.rotate(false);
+            board.right();
+        });
+        assert_eq!(game.board.tetromino(), Some(expected));
+        assert_eq!(game.metrics().hold, Some(T));
+    }
+
+    #[test]
+    fn applies_inputs_immediately_without_buffering() {
+        let mut game = Game::new(
+            1,
+            1,
+            RandomTetromino::from_sequence(&[T, O, I]),
+            RotationSystem::Srs,
+            false,
+            TimingPreset::Guideline.timings(),
+            GameMode::Standard,
+        );
+        assert!(!game.is_buffering_inputs());
+        game.rotate(true);
+        game.hold();
+        update_until_fall(&mut game);
+        assert_eq!(game.board.tetromino(), Some(expected_tetromino(T, |_| {})));
+        assert_eq!(game.metrics().hold, None);
+    }
+}
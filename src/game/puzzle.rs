@@ -0,0 +1,191 @@
+use super::block::BlockState;
+use super::board::{StackRow, BOARD_HEIGHT, BOARD_WIDTH};
+use super::fumen;
+use super::geometry::Rotation;
+use super::tetromino::TetrominoShape;
+use crate::config::config_path;
+use serde::{Deserialize, Serialize};
+
+const CONFIG_NAME: &str = "puzzles";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PuzzleGoal {
+    /// Clear every block from the board
+    PerfectClear,
+    /// Clear 2 lines with a single T spin
+    TSpinDouble,
+    /// Clear a total number of lines
+    ClearLines { lines: u32 },
+}
+
+impl PuzzleGoal {
+    pub fn description(&self) -> String {
+        match self {
+            PuzzleGoal::PerfectClear => "perfect clear".to_string(),
+            PuzzleGoal::TSpinDouble => "t-spin double".to_string(),
+            PuzzleGoal::ClearLines { lines } => format!("clear {} lines", lines),
+        }
+    }
+}
+
+/// An authored board setup to be solved with a fixed sequence of tetrominos
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub name: String,
+    /// Rows of the initial stack from the top down,
+    /// '.' is empty, '#' is garbage and a shape letter is a stacked block of that shape
+    #[serde(default)]
+    pub stack: Vec<String>,
+    /// A fumen to take the initial stack from instead
+    #[serde(default)]
+    pub fumen: Option<String>,
+    pub pieces: Vec<TetrominoShape>,
+    pub goal: PuzzleGoal,
+}
+
+impl Puzzle {
+    fn new(name: &str, stack: &[&str], pieces: &[TetrominoShape], goal: PuzzleGoal) -> Self {
+        Self {
+            name: name.to_string(),
+            stack: stack.iter().map(|row| row.to_string()).collect(),
+            fumen: None,
+            pieces: pieces.to_vec(),
+            goal,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        format!("{} - {}", self.name, self.goal.description())
+    }
+
+    /// Rows of the initial stack from the bottom up
+    pub fn stack_rows(&self) -> Result<Vec<StackRow>, String> {
+        if let Some(fumen) = self.fumen.as_ref() {
+            return fumen::decode(fumen)
+                .map(|page| page.field)
+                .map_err(|e| format!("{}: {}", self.name, e));
+        }
+        if self.stack.len() > BOARD_HEIGHT as usize {
+            return Err(format!("{}: stack is taller than the board", self.name));
+        }
+        self.stack
+            .iter()
+            .rev()
+            .map(|row| {
+                let blocks = row
+                    .chars()
+                    .map(|c| {
+                        parse_block(c).ok_or_else(|| format!("{}: bad block '{}'", self.name, c))
+                    })
+                    .collect::<Result<Vec<BlockState>, String>>()?;
+                blocks
+                    .try_into()
+                    .map_err(|_| format!("{}: rows must be {} wide", self.name, BOARD_WIDTH))
+            })
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.pieces.is_empty() {
+            return Err(format!("{}: no pieces", self.name));
+        }
+        self.stack_rows().map(|_| ())
+    }
+}
+
+fn parse_block(c: char) -> Option<BlockState> {
+    let shape = match c {
+        '.' | ' ' => return Some(BlockState::Empty),
+        '#' => return Some(BlockState::Garbage),
+        c => fumen::char_shape(c)?,
+    };
+    Some(BlockState::Stack(shape, Rotation::North, 0))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzles {
+    pub puzzles: Vec<Puzzle>,
+}
+
+impl Default for Puzzles {
+    fn default() -> Self {
+        Self {
+            puzzles: vec![
+                Puzzle::new(
+                    "two squares",
+                    &["####....##", "####....##"],
+                    &[TetrominoShape::O, TetrominoShape::O],
+                    PuzzleGoal::PerfectClear,
+                ),
+                Puzzle::new(
+                    "overhang",
+                    &["####......", "###...####", "####.#####"],
+                    &[TetrominoShape::T],
+                    PuzzleGoal::TSpinDouble,
+                ),
+                Puzzle::new(
+                    "well",
+                    &["#########.", "#########.", "#########.", "#########."],
+                    &[TetrominoShape::I],
+                    PuzzleGoal::ClearLines { lines: 4 },
+                ),
+            ],
+        }
+    }
+}
+
+impl Puzzles {
+    pub fn load() -> Result<Self, String> {
+        let config_path = config_path(CONFIG_NAME)?;
+        #[cfg(debug_assertions)]
+        println!("loading puzzles: {}", config_path.to_str().unwrap());
+        let result: Self = confy::load_path(config_path).map_err(|e| e.to_string())?;
+        for puzzle in result.puzzles.iter() {
+            puzzle.validate()?;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_puzzles_are_valid() {
+        for puzzle in Puzzles::default().puzzles {
+            assert_eq!(puzzle.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn stack_rows_bottom_up() {
+        let stack = ["T.........", "#########."];
+        let puzzle = Puzzle::new("test", &stack, &[], PuzzleGoal::PerfectClear);
+        let rows = puzzle.stack_rows().unwrap();
+        assert_eq!(rows[0][0], BlockState::Garbage);
+        assert_eq!(rows[0][9], BlockState::Empty);
+        assert_eq!(rows[1][0], BlockState::Stack(TetrominoShape::T, Rotation::North, 0));
+    }
+
+    #[test]
+    fn stack_from_fumen() {
+        let mut puzzle = Puzzle::new("test", &[], &[TetrominoShape::I], PuzzleGoal::PerfectClear);
+        puzzle.fumen = Some("v115@vhAAgH".to_string());
+        let rows = puzzle.stack_rows().unwrap();
+        assert!(rows.iter().flatten().all(|b| *b == BlockState::Empty));
+        puzzle.fumen = Some("nonsense".to_string());
+        assert!(puzzle.validate().is_err());
+    }
+
+    #[test]
+    fn bad_stack() {
+        let pieces = [TetrominoShape::I];
+        let puzzle = Puzzle::new("test", &["###"], &pieces, PuzzleGoal::PerfectClear);
+        assert!(puzzle.validate().is_err());
+        let puzzle = Puzzle::new("test", &["X........."], &pieces, PuzzleGoal::PerfectClear);
+        assert!(puzzle.validate().is_err());
+        let puzzle = Puzzle::new("test", &["#........."], &[], PuzzleGoal::PerfectClear);
+        assert!(puzzle.validate().is_err());
+    }
+}
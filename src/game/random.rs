@@ -6,6 +6,7 @@ use rand::{thread_rng, Rng};
 use rand_chacha::{ChaCha8Rng, ChaChaRng};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use strum::IntoEnumIterator;
 
 pub const PEEK_SIZE: usize = 7;
 const ALL_SHAPES: [TetrominoShape; 7] = [
@@ -18,120 +19,446 @@ const ALL_SHAPES: [TetrominoShape; 7] = [
     TetrominoShape::L,
 ];
 
+/// TGM only deals these as the first piece, S, Z & O would force an overhang
+const TGM_FIRST_SHAPES: [TetrominoShape; 4] = [
+    TetrominoShape::I,
+    TetrominoShape::T,
+    TetrominoShape::J,
+    TetrominoShape::L,
+];
+const TGM_HISTORY: [TetrominoShape; 4] = [
+    TetrominoShape::Z,
+    TetrominoShape::S,
+    TetrominoShape::S,
+    TetrominoShape::Z,
+];
+const TGM_ROLLS: u32 = 6;
+
+/// garbage holes are drawn from a separate stream so receiving garbage does not change the tetromino sequence
+const GARBAGE_STREAM: u64 = 1;
+
 fn rand_shape<R: Rng>(rng: &mut R) -> TetrominoShape {
     ALL_SHAPES[rng.gen_range(0..ALL_SHAPES.len())]
 }
 
-type Seed = <ChaCha8Rng as SeedableRng>::Seed;
+pub type Seed = <ChaCha8Rng as SeedableRng>::Seed;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::IntoStaticStr,
+    strum::EnumIter,
+    strum::EnumString,
+)]
 pub enum RandomMode {
     /// Random tetromino every time
+    #[strum(serialize = "true")]
     True,
     /// All tetrominoes placed in a shuffled "bag" and drawn until the bag is empty, after which a new bag is shuffled
+    #[strum(serialize = "bag")]
     Bag,
+    /// As the bag but with two of every tetromino in each bag
+    #[strum(serialize = "14 bag")]
+    Bag14,
+    /// As the bag but with an extra random tetromino in each bag
+    #[strum(serialize = "7+1 bag")]
+    BagPlusOne,
+    /// TGM style, reroll up to 6 times when the shape is in the history of the last 4 shapes
+    #[strum(serialize = "tgm")]
+    Tgm,
+    /// Classic NES, reroll once when the shape is the same as the last shape
+    #[strum(serialize = "nes")]
+    Nes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GarbageMode {
+    /// The hole stays in the same column, moving every n lines of garbage
+    Clean { lines_per_hole: u32 },
+    /// Every line of garbage has a percentage chance of moving the hole
+    Messy { shift_chance: u32 },
+    /// The hole moves at the start of every attack so each attack is clean
+    Attack,
+}
+
+impl Default for GarbageMode {
+    /// The hole rate from before there were garbage modes
+    fn default() -> Self {
+        Self::Clean { lines_per_hole: 10 }
+    }
 }
 
 impl RandomMode {
-    pub fn build(self, count: usize, min_garbage_per_hole: u32) -> Vec<RandomTetromino> {
-        let mut seed: Seed = Default::default();
-        thread_rng().fill(&mut seed);
+    pub fn names() -> Vec<&'static str> {
+        Self::iter().map(|e| e.into()).collect()
+    }
+
+    /// Every player shares the seed, None for a random seed
+    pub fn build(
+        self,
+        count: usize,
+        garbage_mode: GarbageMode,
+        seed: Option<Seed>,
+    ) -> Vec<RandomTetromino> {
+        let seed = seed.unwrap_or_else(|| {
+            let mut seed: Seed = Default::default();
+            thread_rng().fill(&mut seed);
+            seed
+        });
         (0..count)
-            .map(|_| RandomTetromino::new(self, min_garbage_per_hole, seed))
+            .map(|_| RandomTetromino::new(self, garbage_mode, seed))
             .collect()
     }
+
+    fn randomizer(self) -> Box<dyn Randomizer> {
+        match self {
+            RandomMode::True => Box::new(TrueRandomizer),
+            RandomMode::Bag => Box::new(BagRandomizer::new(1, 0)),
+            RandomMode::Bag14 => Box::new(BagRandomizer::new(2, 0)),
+            RandomMode::BagPlusOne => Box::new(BagRandomizer::new(1, 1)),
+            RandomMode::Tgm => Box::new(HistoryRandomizer::new()),
+            RandomMode::Nes => Box::new(NesRandomizer::new()),
+        }
+    }
 }
 
+/// Generates the next tetromino shape for a random mode
+pub trait Randomizer {
+    fn next(&mut self, rng: &mut ChaChaRng) -> TetrominoShape;
+
+    fn box_clone(&self) -> Box<dyn Randomizer>;
+
+    /// Snapshot for saving an in-progress game
+    fn state(&self) -> RandomizerState;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RandomizerState {
+    True(TrueRandomizer),
+    Bag(BagRandomizer),
+    History(HistoryRandomizer),
+    Nes(NesRandomizer),
+}
+
+impl RandomizerState {
+    fn into_randomizer(self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerState::True(randomizer) => Box::new(randomizer),
+            RandomizerState::Bag(randomizer) => Box::new(randomizer),
+            RandomizerState::History(randomizer) => Box::new(randomizer),
+            RandomizerState::Nes(randomizer) => Box::new(randomizer),
+        }
+    }
+}
+
+impl Clone for Box<dyn Randomizer> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrueRandomizer;
+
+impl Randomizer for TrueRandomizer {
+    fn next(&mut self, rng: &mut ChaChaRng) -> TetrominoShape {
+        rand_shape(rng)
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> RandomizerState {
+        RandomizerState::True(self.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BagRandomizer {
+    copies: usize,
+    extras: usize,
+    bag: Vec<TetrominoShape>,
+}
+
+impl BagRandomizer {
+    fn new(copies: usize, extras: usize) -> Self {
+        Self {
+            copies,
+            extras,
+            bag: vec![],
+        }
+    }
+}
+
+impl Randomizer for BagRandomizer {
+    fn next(&mut self, rng: &mut ChaChaRng) -> TetrominoShape {
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend_from_slice(&ALL_SHAPES);
+            }
+            for _ in 0..self.extras {
+                self.bag.push(rand_shape(rng));
+            }
+            self.bag.shuffle(rng);
+        }
+        self.bag.pop().unwrap()
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> RandomizerState {
+        RandomizerState::Bag(self.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HistoryRandomizer {
+    history: VecDeque<TetrominoShape>,
+    first: bool,
+}
+
+impl HistoryRandomizer {
+    fn new() -> Self {
+        Self {
+            history: VecDeque::from(TGM_HISTORY),
+            first: true,
+        }
+    }
+}
+
+impl Randomizer for HistoryRandomizer {
+    fn next(&mut self, rng: &mut ChaChaRng) -> TetrominoShape {
+        let result = if self.first {
+            self.first = false;
+            *TGM_FIRST_SHAPES.choose(rng).unwrap()
+        } else {
+            let mut shape = rand_shape(rng);
+            for _ in 1..TGM_ROLLS {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = rand_shape(rng);
+            }
+            shape
+        };
+        self.history.pop_front();
+        self.history.push_back(result);
+        result
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> RandomizerState {
+        RandomizerState::History(self.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NesRandomizer {
+    last: Option<TetrominoShape>,
+}
+
+impl NesRandomizer {
+    fn new() -> Self {
+        Self { last: None }
+    }
+}
+
+impl Randomizer for NesRandomizer {
+    fn next(&mut self, rng: &mut ChaChaRng) -> TetrominoShape {
+        // roll an 8 sided die, the 8th side and repeats of the last shape cause a single re-roll
+        let index = rng.gen_range(0..ALL_SHAPES.len() + 1);
+        let result = match ALL_SHAPES.get(index) {
+            Some(shape) if Some(*shape) != self.last => *shape,
+            _ => rand_shape(rng),
+        };
+        self.last = Some(result);
+        result
+    }
+
+    fn box_clone(&self) -> Box<dyn Randomizer> {
+        Box::new(self.clone())
+    }
+
+    fn state(&self) -> RandomizerState {
+        RandomizerState::Nes(self.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct RandomTetromino {
-    random_mode: RandomMode,
-    min_garbage_per_hole: u32, // move the garbage hole every n garbage
+    /// None for a fixed sequence that runs out
+    #[serde(with = "randomizer_state")]
+    randomizer: Option<Box<dyn Randomizer>>,
+    garbage_mode: GarbageMode,
     garbage_since_last_hole: u32,
     current_garbage_hole: u32,
+    #[serde(with = "rng_state")]
     rng: ChaChaRng,
+    #[serde(with = "rng_state")]
+    garbage_rng: ChaChaRng,
     queue: VecDeque<TetrominoShape>,
 }
 
+mod randomizer_state {
+    use super::{Randomizer, RandomizerState};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        randomizer: &Option<Box<dyn Randomizer>>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        randomizer.as_ref().map(|r| r.state()).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Box<dyn Randomizer>>, D::Error> {
+        Ok(Option::<RandomizerState>::deserialize(d)?.map(RandomizerState::into_randomizer))
+    }
+}
+
+/// The position in the stream is saved with the seed so the sequence carries on where it left off
+mod rng_state {
+    use super::Seed;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct RngState {
+        seed: Seed,
+        stream: u64,
+        word_pos: u128,
+    }
+
+    pub fn serialize<S: Serializer>(rng: &ChaChaRng, s: S) -> Result<S::Ok, S::Error> {
+        RngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos: rng.get_word_pos(),
+        }
+        .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<ChaChaRng, D::Error> {
+        let state = RngState::deserialize(d)?;
+        let mut rng = ChaChaRng::from_seed(state.seed);
+        rng.set_stream(state.stream);
+        rng.set_word_pos(state.word_pos);
+        Ok(rng)
+    }
+}
+
 impl RandomTetromino {
-    pub fn new(random_mode: RandomMode, min_garbage_per_hole: u32, seed: Seed) -> Self {
+    pub fn new(random_mode: RandomMode, garbage_mode: GarbageMode, seed: Seed) -> Self {
         let mut rng = ChaChaRng::from_seed(seed);
-        let current_garbage_hole = rng.gen_range(0..BOARD_WIDTH);
-        match random_mode {
-            RandomMode::True => {
-                let queue = (0..PEEK_SIZE)
-                    .map(|_| rand_shape(&mut rng))
-                    .collect::<VecDeque<TetrominoShape>>();
-                Self {
-                    random_mode,
-                    min_garbage_per_hole,
-                    garbage_since_last_hole: 0,
-                    current_garbage_hole,
-                    rng,
-                    queue,
-                }
-            }
-            RandomMode::Bag => {
-                let mut result = Self {
-                    random_mode,
-                    min_garbage_per_hole,
-                    garbage_since_last_hole: 0,
-                    current_garbage_hole,
-                    rng,
-                    queue: VecDeque::new(),
-                };
-                result.assert_bags();
-                result
-            }
+        let mut garbage_rng = ChaChaRng::from_seed(seed);
+        garbage_rng.set_stream(GARBAGE_STREAM);
+        let current_garbage_hole = garbage_rng.gen_range(0..BOARD_WIDTH);
+        let mut randomizer = random_mode.randomizer();
+        let queue = (0..PEEK_SIZE)
+            .map(|_| randomizer.next(&mut rng))
+            .collect::<VecDeque<TetrominoShape>>();
+        Self {
+            randomizer: Some(randomizer),
+            garbage_mode,
+            garbage_since_last_hole: 0,
+            current_garbage_hole,
+            rng,
+            garbage_rng,
+            queue,
         }
     }
 
-    pub fn next_garbage_hole(&mut self) -> u32 {
-        let result = self.current_garbage_hole;
-        self.garbage_since_last_hole += 1;
-        if self.garbage_since_last_hole >= self.min_garbage_per_hole {
-            self.garbage_since_last_hole = 0;
-            self.current_garbage_hole = self.rng.gen_range(0..BOARD_WIDTH);
-        }
+    /// A fixed sequence of shapes e.g. for a puzzle, the queue is exhausted once they are all dealt
+    pub fn from_sequence(shapes: &[TetrominoShape]) -> Self {
+        let mut result = Self::new(RandomMode::True, GarbageMode::Attack, Default::default());
+        result.randomizer = None;
+        result.queue = shapes.iter().copied().collect();
         result
     }
 
-    pub fn next(&mut self) -> TetrominoShape {
-        match self.random_mode {
-            RandomMode::True => self.next_true(),
-            RandomMode::Bag => self.next_bag(),
+    /// Deals these shapes next e.g. from an imported fumen, the randomizer carries on after them
+    pub fn preset_queue(&mut self, shapes: &[TetrominoShape]) {
+        self.queue = shapes.iter().copied().collect();
+        if let Some(randomizer) = self.randomizer.as_mut() {
+            while self.queue.len() < PEEK_SIZE {
+                self.queue.push_back(randomizer.next(&mut self.rng));
+            }
         }
     }
 
-    fn next_true(&mut self) -> TetrominoShape {
-        self.queue.push_back(rand_shape(&mut self.rng));
-        self.queue.pop_front().unwrap()
+    pub fn seed(&self) -> Seed {
+        self.rng.get_seed()
     }
 
-    fn next_bag(&mut self) -> TetrominoShape {
-        let result = self.queue.pop_front().unwrap();
-        self.assert_bags();
-        result
+    pub fn is_exhausted(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Generates the holes for every line of a single garbage attack
+    pub fn next_garbage_attack(&mut self, lines: u32) -> Vec<u32> {
+        if self.garbage_mode == GarbageMode::Attack {
+            self.current_garbage_hole = self.garbage_rng.gen_range(0..BOARD_WIDTH);
+        }
+        (0..lines).map(|_| self.next_garbage_hole()).collect()
+    }
+
+    /// Generates the holes of a pre-filled dig board, every line has its hole in a different column
+    pub fn next_dig_holes(&mut self, lines: u32) -> Vec<u32> {
+        (0..lines)
+            .map(|_| {
+                let shift = self.garbage_rng.gen_range(1..BOARD_WIDTH);
+                self.current_garbage_hole = (self.current_garbage_hole + shift) % BOARD_WIDTH;
+                self.current_garbage_hole
+            })
+            .collect()
     }
 
-    pub fn peek(&self) -> [TetrominoShape; PEEK_SIZE] {
-        self.queue
-            .iter()
-            .take(PEEK_SIZE)
-            .copied()
-            .collect::<Vec<TetrominoShape>>()
-            .try_into()
-            .unwrap()
-    }
-
-    fn assert_bags(&mut self) {
-        while self.queue.len() <= PEEK_SIZE {
-            let bag = ALL_SHAPES
-                .choose_multiple(&mut self.rng, ALL_SHAPES.len())
-                .cloned()
-                .collect::<Vec<TetrominoShape>>();
-            for shape in bag {
-                self.queue.push_back(shape);
+    fn next_garbage_hole(&mut self) -> u32 {
+        match self.garbage_mode {
+            GarbageMode::Clean { lines_per_hole } => {
+                let result = self.current_garbage_hole;
+                self.garbage_since_last_hole += 1;
+                if self.garbage_since_last_hole >= lines_per_hole {
+                    self.garbage_since_last_hole = 0;
+                    self.current_garbage_hole = self.garbage_rng.gen_range(0..BOARD_WIDTH);
+                }
+                result
+            }
+            GarbageMode::Messy { shift_chance } => {
+                if self.garbage_rng.gen_range(0..100) < shift_chance {
+                    // shift to any other column
+                    let shift = self.garbage_rng.gen_range(1..BOARD_WIDTH);
+                    self.current_garbage_hole = (self.current_garbage_hole + shift) % BOARD_WIDTH;
+                }
+                self.current_garbage_hole
             }
+            GarbageMode::Attack => self.current_garbage_hole,
+        }
+    }
+
+    pub fn next(&mut self) -> TetrominoShape {
+        if let Some(randomizer) = self.randomizer.as_mut() {
+            self.queue.push_back(randomizer.next(&mut self.rng));
         }
+        self.queue.pop_front().expect("tetromino sequence exhausted")
+    }
+
+    /// Upcoming shapes, a fixed sequence leaves the slots after its last shape empty
+    pub fn peek(&self) -> [Option<TetrominoShape>; PEEK_SIZE] {
+        std::array::from_fn(|i| self.queue.get(i).copied())
     }
 }
 
@@ -144,13 +471,15 @@ mod tests {
         (0..n).map(|_| random.next()).collect()
     }
 
+    const CLEAN_GARBAGE: GarbageMode = GarbageMode::Clean { lines_per_hole: 10 };
+
     fn next_n_holes(random: &mut RandomTetromino, n: usize) -> Vec<u32> {
         (0..n).map(|_| random.next_garbage_hole()).collect()
     }
 
     #[test]
     fn bag_random() {
-        let mut random = RandomMode::Bag.build(1, 10).pop().unwrap();
+        let mut random = RandomMode::Bag.build(1, CLEAN_GARBAGE, None).pop().unwrap();
 
         // chunk into 3 bags of 7 shapes (arrays make it easier for creating the sets)
         let bags: Vec<[TetrominoShape; 7]> = next_n(&mut random, 21)
@@ -171,16 +500,16 @@ mod tests {
 
     #[test]
     fn bag_random_peek() {
-        let mut random = RandomMode::Bag.build(1, 10).pop().unwrap();
+        let mut random = RandomMode::Bag.build(1, CLEAN_GARBAGE, None).pop().unwrap();
         let peek = random.peek();
         let observed: [TetrominoShape; PEEK_SIZE] =
             next_n(&mut random, PEEK_SIZE).try_into().unwrap();
-        assert_eq!(observed, peek);
+        assert_eq!(observed.map(Some), peek);
     }
 
     #[test]
     fn true_random() {
-        let mut random = RandomMode::True.build(1, 10).pop().unwrap();
+        let mut random = RandomMode::True.build(1, CLEAN_GARBAGE, None).pop().unwrap();
         let observed: [TetrominoShape; 1000] = next_n(&mut random, 1000).try_into().unwrap();
         // should generate all shapes in 1000 tries
         assert_eq!(HashSet::from(observed), HashSet::from(ALL_SHAPES));
@@ -188,24 +517,187 @@ mod tests {
 
     #[test]
     fn true_random_peek() {
-        let mut random = RandomMode::True.build(1, 10).pop().unwrap();
+        let mut random = RandomMode::True.build(1, CLEAN_GARBAGE, None).pop().unwrap();
         let peek = random.peek();
         let observed: [TetrominoShape; PEEK_SIZE] =
             next_n(&mut random, PEEK_SIZE).try_into().unwrap();
-        assert_eq!(observed, peek);
+        assert_eq!(observed.map(Some), peek);
+    }
+
+    #[test]
+    fn bag_14_random() {
+        let mut random = RandomMode::Bag14.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+        for bag in next_n(&mut random, 28).chunks(14) {
+            for shape in ALL_SHAPES {
+                assert_eq!(bag.iter().filter(|s| **s == shape).count(), 2);
+            }
+        }
+    }
+
+    #[test]
+    fn bag_plus_one_random() {
+        let mut random = RandomMode::BagPlusOne.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+        for bag in next_n(&mut random, 24).chunks(8) {
+            assert_eq!(bag.iter().copied().collect::<HashSet<_>>(), HashSet::from(ALL_SHAPES));
+        }
+    }
+
+    #[test]
+    fn tgm_random_first_shape() {
+        for _ in 0..100 {
+            let mut random = RandomMode::Tgm.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+            assert!(TGM_FIRST_SHAPES.contains(&random.next()));
+        }
+    }
+
+    #[test]
+    fn tgm_random() {
+        let mut random = RandomMode::Tgm.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+        let observed = next_n(&mut random, 1000);
+        assert_eq!(observed.iter().copied().collect::<HashSet<_>>(), HashSet::from(ALL_SHAPES));
+        // history makes immediate repeats much less likely than 1/7
+        let repeats = observed.windows(2).filter(|w| w[0] == w[1]).count();
+        assert!(repeats < 1000 / 14, "{} repeats", repeats);
+    }
+
+    #[test]
+    fn nes_random() {
+        let mut random = RandomMode::Nes.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+        let observed = next_n(&mut random, 1000);
+        assert_eq!(observed.iter().copied().collect::<HashSet<_>>(), HashSet::from(ALL_SHAPES));
+        // repeats are possible after a re-roll but much less likely than 1/7
+        let repeats = observed.windows(2).filter(|w| w[0] == w[1]).count();
+        assert!(repeats < 1000 / 7, "{} repeats", repeats);
+    }
+
+    #[test]
+    fn all_random_modes_peek() {
+        for mode in RandomMode::iter() {
+            let mut random = mode.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+            for _ in 0..3 {
+                let peek = random.peek();
+                let observed: [TetrominoShape; PEEK_SIZE] =
+                    next_n(&mut random, PEEK_SIZE).try_into().unwrap();
+                assert_eq!(observed.map(Some), peek, "{:?}", mode);
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_shapes() {
+        for mode in RandomMode::iter() {
+            let mut randoms = mode.build(2, CLEAN_GARBAGE, None);
+            let observed_2 = next_n(&mut randoms.pop().unwrap(), 100);
+            let observed_1 = next_n(&mut randoms.pop().unwrap(), 100);
+            assert_eq!(observed_1, observed_2, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn reused_seed_repeats_shapes() {
+        for mode in RandomMode::iter() {
+            let mut random = mode.build(1, CLEAN_GARBAGE, None).pop().unwrap();
+            let seed = random.seed();
+            let observed = next_n(&mut random, 100);
+            let mut restarted = mode.build(1, CLEAN_GARBAGE, Some(seed)).pop().unwrap();
+            assert_eq!(random.seed(), seed);
+            assert_eq!(next_n(&mut restarted, 100), observed, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn fixed_sequence() {
+        let shapes = [TetrominoShape::T, TetrominoShape::I];
+        let mut random = RandomTetromino::from_sequence(&shapes);
+        assert_eq!(random.peek()[..3], [Some(TetrominoShape::T), Some(TetrominoShape::I), None]);
+        assert_eq!(next_n(&mut random, 2), shapes);
+        assert!(random.is_exhausted());
+        assert_eq!(random.peek(), [None; PEEK_SIZE]);
     }
 
     #[test]
     fn static_garbage_hole() {
-        let mut random = RandomMode::True.build(1, 100).pop().unwrap();
+        let mut random = RandomMode::True
+            .build(1, GarbageMode::Clean { lines_per_hole: 100 }, None)
+            .pop()
+            .unwrap();
         let observed: [u32; 100] = next_n_holes(&mut random, 100).try_into().unwrap();
         assert_eq!(HashSet::from(observed).len(), 1);
     }
 
     #[test]
     fn dynamic_garbage_hole() {
-        let mut random = RandomMode::True.build(1, 1).pop().unwrap();
+        let mut random = RandomMode::True
+            .build(1, GarbageMode::Clean { lines_per_hole: 1 }, None)
+            .pop()
+            .unwrap();
         let observed: [u32; 100] = next_n_holes(&mut random, 100).try_into().unwrap();
         assert!(HashSet::from(observed).len() > 1);
     }
+
+    #[test]
+    fn messy_garbage_hole() {
+        let mut random = RandomMode::True
+            .build(1, GarbageMode::Messy { shift_chance: 100 }, None)
+            .pop()
+            .unwrap();
+        let observed = next_n_holes(&mut random, 100);
+        assert!(observed.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    #[test]
+    fn messy_garbage_hole_never_shifts() {
+        let mut random = RandomMode::True
+            .build(1, GarbageMode::Messy { shift_chance: 0 }, None)
+            .pop()
+            .unwrap();
+        let observed: [u32; 100] = next_n_holes(&mut random, 100).try_into().unwrap();
+        assert_eq!(HashSet::from(observed).len(), 1);
+    }
+
+    #[test]
+    fn dig_holes() {
+        let mut randoms = RandomMode::Bag.build(2, CLEAN_GARBAGE, None);
+        let first = randoms[0].next_dig_holes(100);
+        assert!(first.windows(2).all(|w| w[0] != w[1]));
+        assert_eq!(first, randoms[1].next_dig_holes(100));
+    }
+
+    #[test]
+    fn attack_garbage_hole() {
+        let mut random = RandomMode::True.build(1, GarbageMode::Attack, None).pop().unwrap();
+        let attacks: Vec<Vec<u32>> = (0..100).map(|_| random.next_garbage_attack(4)).collect();
+        for attack in attacks.iter() {
+            assert_eq!(HashSet::<&u32>::from_iter(attack.iter()).len(), 1);
+        }
+        assert!(HashSet::<u32>::from_iter(attacks.iter().map(|a| a[0])).len() > 1);
+    }
+
+    #[test]
+    fn garbage_does_not_change_shapes() {
+        let mut randoms = RandomMode::Bag.build(2, GarbageMode::Attack, None);
+        let mut random_2 = randoms.pop().unwrap();
+        let mut random_1 = randoms.pop().unwrap();
+        random_1.next_garbage_attack(10);
+        assert_eq!(next_n(&mut random_1, 100), next_n(&mut random_2, 100));
+    }
+
+    /// Suspended matches carry on with the same shapes & garbage as if they had never stopped
+    #[test]
+    fn round_trips_through_a_save() {
+        for random_mode in RandomMode::iter() {
+            for garbage_mode in [CLEAN_GARBAGE, GarbageMode::Messy { shift_chance: 50 }] {
+                let mut random = random_mode.build(1, garbage_mode, None).pop().unwrap();
+                next_n(&mut random, 10);
+                random.next_garbage_attack(3);
+
+                let yaml = serde_yaml::to_string(&random).unwrap();
+                let mut loaded = serde_yaml::from_str::<RandomTetromino>(&yaml).unwrap();
+                assert_eq!(loaded.peek(), random.peek(), "{:?}", random_mode);
+                assert_eq!(next_n(&mut loaded, 50), next_n(&mut random, 50), "{:?}", random_mode);
+                assert_eq!(next_n_holes(&mut loaded, 50), next_n_holes(&mut random, 50));
+                assert_eq!(loaded.next_dig_holes(10), random.next_dig_holes(10));
+            }
+        }
+    }
 }
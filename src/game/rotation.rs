@@ -0,0 +1,244 @@
+use super::geometry::{Point, Rotation};
+use super::tetromino::{Minos, TetrominoMeta, TetrominoShape};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+type Kicks = [(i32, i32); 5];
+
+/// https://tetris.wiki/SRS#SRS.2B (y axis flipped to point up)
+const SRS_PLUS_I_KICKS_NORTH_EAST: Kicks = [(0, 0), (1, 0), (-2, 0), (-2, -1), (1, 2)];
+const SRS_PLUS_I_KICKS_EAST_NORTH: Kicks = [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)];
+const SRS_PLUS_I_KICKS_EAST_SOUTH: Kicks = [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)];
+const SRS_PLUS_I_KICKS_SOUTH_EAST: Kicks = [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)];
+const SRS_PLUS_I_KICKS_SOUTH_WEST: Kicks = [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)];
+const SRS_PLUS_I_KICKS_WEST_SOUTH: Kicks = [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)];
+const SRS_PLUS_I_KICKS_WEST_NORTH: Kicks = [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)];
+const SRS_PLUS_I_KICKS_NORTH_WEST: Kicks = [(0, 0), (-1, 0), (2, 0), (2, -1), (-1, 2)];
+
+/// ARS tries the basic rotation, then one to the right, then one to the left
+const ARS_KICKS: [Point; 3] = [Point::new(0, 0), Point::new(1, 0), Point::new(-1, 0)];
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::IntoStaticStr,
+    strum::EnumIter,
+    strum::EnumString,
+)]
+pub enum RotationSystem {
+    /// Super Rotation System, the guideline standard
+    #[default]
+    #[strum(serialize = "srs")]
+    Srs,
+    /// SRS with symmetrical I kicks
+    #[strum(serialize = "srs+")]
+    SrsPlus,
+    /// Arika Rotation System from TGM, flat side up spawns and simple left/right kicks
+    #[strum(serialize = "ars")]
+    Ars,
+    /// Classic NES, flat side up, right-handed spawns with no wall kicks
+    #[strum(serialize = "classic")]
+    Classic,
+}
+
+impl RotationSystem {
+    pub fn names() -> Vec<&'static str> {
+        Self::iter().map(|e| e.into()).collect()
+    }
+
+    /// Offset from the SRS spawn point
+    pub fn spawn_offset(&self, shape: TetrominoShape) -> Point {
+        let flat_side_up = !matches!(shape, TetrominoShape::I | TetrominoShape::O);
+        match self {
+            RotationSystem::Srs | RotationSystem::SrsPlus => Point::new(0, 0),
+            // bump flat side up spawns back up above the skyline
+            RotationSystem::Ars if flat_side_up => Point::new(0, 1),
+            RotationSystem::Classic if flat_side_up => Point::new(1, 1),
+            _ => Point::new(0, 0),
+        }
+    }
+
+    /// The rotation used to render the minos of a tetromino in this rotation state.
+    /// All rotation systems are modelled as an SRS rotation plus an offset so that sprites line up.
+    pub fn sprite_rotation(&self, shape: TetrominoShape, rotation: Rotation) -> Rotation {
+        self.orientation(shape, rotation).0
+    }
+
+    /// Minos of a tetromino in this rotation state, relative to the tetromino position
+    pub fn minos(&self, meta: &TetrominoMeta, rotation: Rotation) -> Minos {
+        let (srs_rotation, offset) = self.orientation(meta.shape(), rotation);
+        meta.rotated_minos(srs_rotation).map(|p| p + offset)
+    }
+
+    /// Translations to try in order when rotating a tetromino,
+    /// these include any offset between the two orientations
+    pub fn kicks(
+        &self,
+        meta: &TetrominoMeta,
+        from_rotation: Rotation,
+        to_rotation: Rotation,
+    ) -> Vec<Point> {
+        let half_turn = to_rotation == from_rotation.flip();
+        match self {
+            RotationSystem::Srs | RotationSystem::SrsPlus if half_turn => {
+                meta.half_turn_kicks(from_rotation)
+            }
+            RotationSystem::Srs => meta.wall_kicks(from_rotation, to_rotation),
+            RotationSystem::SrsPlus if meta.shape() == TetrominoShape::I => {
+                let srs_kicks = meta.wall_kicks(from_rotation, to_rotation);
+                Self::srs_plus_i_kicks(from_rotation, to_rotation)
+                    .iter()
+                    .map(|kick| srs_kicks[0] + Point::from(*kick))
+                    .collect()
+            }
+            RotationSystem::SrsPlus => meta.wall_kicks(from_rotation, to_rotation),
+            RotationSystem::Ars => match meta.shape() {
+                TetrominoShape::I | TetrominoShape::O => vec![Point::new(0, 0)],
+                _ => ARS_KICKS.to_vec(),
+            },
+            RotationSystem::Classic => vec![Point::new(0, 0)],
+        }
+    }
+
+    fn orientation(&self, shape: TetrominoShape, rotation: Rotation) -> (Rotation, Point) {
+        match self {
+            RotationSystem::Srs | RotationSystem::SrsPlus => (rotation, Point::new(0, 0)),
+            RotationSystem::Ars | RotationSystem::Classic => {
+                let vertical = matches!(rotation, Rotation::East | Rotation::West);
+                match shape {
+                    TetrominoShape::O => (Rotation::North, Point::new(0, 0)),
+                    // the SRS I offsets shift the east orientation one to the right
+                    TetrominoShape::I if vertical => (Rotation::East, Point::new(1, 0)),
+                    TetrominoShape::I => (Rotation::North, Point::new(0, 0)),
+                    TetrominoShape::S if vertical => (Rotation::West, Point::new(0, 0)),
+                    TetrominoShape::Z if vertical => (Rotation::East, Point::new(0, 0)),
+                    TetrominoShape::S | TetrominoShape::Z => (Rotation::North, Point::new(0, -1)),
+                    // T, J & L are flipped so they spawn flat side up
+                    _ => match rotation {
+                        Rotation::North => (Rotation::South, Point::new(0, 0)),
+                        Rotation::East => (Rotation::West, Point::new(0, 0)),
+                        Rotation::South => (Rotation::North, Point::new(0, -1)),
+                        Rotation::West => (Rotation::East, Point::new(0, 0)),
+                    },
+                }
+            }
+        }
+    }
+
+    fn srs_plus_i_kicks(from_rotation: Rotation, to_rotation: Rotation) -> Kicks {
+        match (from_rotation, to_rotation) {
+            (Rotation::North, Rotation::East) => SRS_PLUS_I_KICKS_NORTH_EAST,
+            (Rotation::East, Rotation::North) => SRS_PLUS_I_KICKS_EAST_NORTH,
+            (Rotation::East, Rotation::South) => SRS_PLUS_I_KICKS_EAST_SOUTH,
+            (Rotation::South, Rotation::East) => SRS_PLUS_I_KICKS_SOUTH_EAST,
+            (Rotation::South, Rotation::West) => SRS_PLUS_I_KICKS_SOUTH_WEST,
+            (Rotation::West, Rotation::South) => SRS_PLUS_I_KICKS_WEST_SOUTH,
+            (Rotation::West, Rotation::North) => SRS_PLUS_I_KICKS_WEST_NORTH,
+            (Rotation::North, Rotation::West) => SRS_PLUS_I_KICKS_NORTH_WEST,
+            _ => [(0, 0); 5],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srs_plus_i_kicks() {
+        let meta = TetrominoShape::I.meta();
+        assert_eq!(
+            RotationSystem::SrsPlus.kicks(meta, Rotation::North, Rotation::East),
+            [
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(-1, 0),
+                Point::new(-1, -1),
+                Point::new(2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn srs_plus_other_kicks_are_srs() {
+        let meta = TetrominoShape::J.meta();
+        assert_eq!(
+            RotationSystem::SrsPlus.kicks(meta, Rotation::North, Rotation::East),
+            meta.wall_kicks(Rotation::North, Rotation::East)
+        );
+    }
+
+    #[test]
+    fn srs_half_turn_kicks() {
+        let meta = TetrominoShape::T.meta();
+        assert_eq!(
+            RotationSystem::Srs.kicks(meta, Rotation::East, Rotation::West),
+            meta.half_turn_kicks(Rotation::East)
+        );
+    }
+
+    #[test]
+    fn ars_t_spawns_flat_side_up() {
+        let meta = TetrominoShape::T.meta();
+        assert_eq!(
+            RotationSystem::Ars.minos(meta, Rotation::North),
+            [
+                Point::new(2, 1),
+                Point::new(1, 1),
+                Point::new(0, 1),
+                Point::new(1, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn ars_s_vertical_is_left_aligned() {
+        let meta = TetrominoShape::S.meta();
+        let east = RotationSystem::Ars.minos(meta, Rotation::East);
+        assert_eq!(east, RotationSystem::Ars.minos(meta, Rotation::West));
+        assert_eq!(east.iter().map(|p| p.x).min(), Some(0));
+    }
+
+    #[test]
+    fn ars_two_state_pieces() {
+        for shape in [TetrominoShape::I, TetrominoShape::S, TetrominoShape::Z] {
+            let meta = shape.meta();
+            assert_eq!(
+                RotationSystem::Ars.minos(meta, Rotation::North),
+                RotationSystem::Ars.minos(meta, Rotation::South),
+                "{:?}",
+                shape
+            );
+        }
+    }
+
+    #[test]
+    fn ars_i_never_kicks() {
+        assert_eq!(
+            RotationSystem::Ars.kicks(TetrominoShape::I.meta(), Rotation::North, Rotation::East),
+            [Point::new(0, 0)]
+        );
+    }
+
+    #[test]
+    fn classic_never_kicks() {
+        for shape in TetrominoShape::ALL {
+            assert_eq!(
+                RotationSystem::Classic.kicks(shape.meta(), Rotation::North, Rotation::East),
+                [Point::new(0, 0)]
+            );
+        }
+    }
+
+    #[test]
+    fn classic_spawns_right_handed() {
+        assert_eq!(RotationSystem::Classic.spawn_offset(TetrominoShape::T), Point::new(1, 1));
+        assert_eq!(RotationSystem::Classic.spawn_offset(TetrominoShape::I), Point::new(0, 0));
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const START_INTERVAL: Duration = Duration::from_secs(8);
+const MIN_INTERVAL: Duration = Duration::from_millis(1500);
+const ACCELERATION: f64 = 0.95; // each garbage row arrives 5% sooner than the last
+
+/// Garbage rows rising on an accelerating timer in survival mode
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SurvivalTimer {
+    interval: Duration,
+    elapsed: Duration,
+}
+
+impl Default for SurvivalTimer {
+    fn default() -> Self {
+        Self {
+            interval: START_INTERVAL,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl SurvivalTimer {
+    /// Number of garbage rows that are due after the delta
+    pub fn update(&mut self, delta: Duration) -> u32 {
+        self.elapsed += delta;
+        let mut rows = 0;
+        while self.elapsed >= self.interval {
+            self.elapsed -= self.interval;
+            self.interval = self.interval.mul_f64(ACCELERATION).max(MIN_INTERVAL);
+            rows += 1;
+        }
+        rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_row_after_start_interval() {
+        let mut timer = SurvivalTimer::default();
+        assert_eq!(timer.update(START_INTERVAL - Duration::from_millis(1)), 0);
+        assert_eq!(timer.update(Duration::from_millis(1)), 1);
+    }
+
+    #[test]
+    fn accelerates() {
+        let mut timer = SurvivalTimer::default();
+        timer.update(START_INTERVAL);
+        assert!(timer.interval < START_INTERVAL);
+    }
+
+    #[test]
+    fn never_faster_than_min_interval() {
+        let mut timer = SurvivalTimer::default();
+        for _ in 0..1000 {
+            timer.update(Duration::from_secs(1));
+        }
+        assert_eq!(timer.interval, MIN_INTERVAL);
+    }
+}
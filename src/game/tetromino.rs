@@ -1,8 +1,10 @@
 use super::geometry::{Point, Rotation};
+use super::rotation::RotationSystem;
 #[allow(unused_imports)]
 use bitflags::{bitflags, Flags};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum TetrominoShape {
     /// XXXX
     #[default]
@@ -79,6 +81,42 @@ struct Offset(i32, i32);
 
 type TetrominoOffsets = [Offset; 5];
 
+/// 180 kicks from SRS+ https://tetris.wiki/SRS#SRS.2B (y axis flipped to point up)
+/// I only uses the first two, O never kicks
+type HalfTurnKicks = [Offset; 6];
+const HALF_TURN_KICKS_NORTH: HalfTurnKicks = [
+    Offset(0, 0),
+    Offset(0, 1),
+    Offset(1, 1),
+    Offset(-1, 1),
+    Offset(1, 0),
+    Offset(-1, 0),
+];
+const HALF_TURN_KICKS_EAST: HalfTurnKicks = [
+    Offset(0, 0),
+    Offset(1, 0),
+    Offset(1, 2),
+    Offset(1, 1),
+    Offset(0, 2),
+    Offset(0, 1),
+];
+const HALF_TURN_KICKS_SOUTH: HalfTurnKicks = [
+    Offset(0, 0),
+    Offset(0, -1),
+    Offset(-1, -1),
+    Offset(1, -1),
+    Offset(-1, 0),
+    Offset(1, 0),
+];
+const HALF_TURN_KICKS_WEST: HalfTurnKicks = [
+    Offset(0, 0),
+    Offset(-1, 0),
+    Offset(-1, 2),
+    Offset(-1, 1),
+    Offset(0, 2),
+    Offset(0, 1),
+];
+
 /// https://tetris.wiki/Super_Rotation_System
 const TETROMINO_OFFSETS_NORTH: TetrominoOffsets = [
     Offset(0, 0),
@@ -187,6 +225,10 @@ pub struct TetrominoMeta {
 }
 
 impl TetrominoMeta {
+    pub fn shape(&self) -> TetrominoShape {
+        self.shape
+    }
+
     pub fn wall_kicks(&self, from_rotation: Rotation, to_rotation: Rotation) -> Vec<Point> {
         let offsets_from = self.shape.offsets(&from_rotation);
         let offsets_to = self.shape.offsets(&to_rotation);
@@ -204,6 +246,27 @@ impl TetrominoMeta {
         }
     }
 
+    pub fn half_turn_kicks(&self, from_rotation: Rotation) -> Vec<Point> {
+        // the offset between true rotation and the SRS orientations is the first wall kick
+        let base = self.wall_kicks(from_rotation, from_rotation.flip())[0];
+        let kicks = match from_rotation {
+            Rotation::North => HALF_TURN_KICKS_NORTH,
+            Rotation::East => HALF_TURN_KICKS_EAST,
+            Rotation::South => HALF_TURN_KICKS_SOUTH,
+            Rotation::West => HALF_TURN_KICKS_WEST,
+        };
+        let count = match self.shape {
+            TetrominoShape::O => 1,
+            TetrominoShape::I => 2,
+            _ => kicks.len(),
+        };
+        kicks
+            .iter()
+            .take(count)
+            .map(|kick| base + Point::new(kick.0, kick.1))
+            .collect()
+    }
+
     pub fn rotated_minos(&self, rotation: Rotation) -> Minos {
         if rotation == Rotation::North {
             return self.minos;
@@ -398,9 +461,11 @@ const Z: TetrominoMeta = TetrominoMeta {
     bounding_box: 3,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tetromino {
+    #[serde(with = "meta_by_shape")]
     meta: TetrominoMeta,
+    rotation_system: RotationSystem,
     position: Point,
     rotation: Rotation,
     minos: Minos,
@@ -408,27 +473,73 @@ pub struct Tetromino {
     y_min: i32,
 }
 
+/// Tetromino meta is static so is saved as just its shape
+mod meta_by_shape {
+    use super::{TetrominoMeta, TetrominoShape};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(meta: &TetrominoMeta, s: S) -> Result<S::Ok, S::Error> {
+        meta.shape().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TetrominoMeta, D::Error> {
+        TetrominoShape::deserialize(d).map(|shape| *shape.meta())
+    }
+}
+
 impl Tetromino {
-    pub fn new(shape: TetrominoShape) -> Self {
+    pub fn new(shape: TetrominoShape, rotation_system: RotationSystem) -> Self {
         let meta = shape.meta();
+        let spawn_point = meta.spawn_point + rotation_system.spawn_offset(shape);
         Self {
             meta: *meta,
-            position: meta.spawn_point,
+            rotation_system,
+            position: spawn_point,
             rotation: Rotation::North,
-            minos: meta
-                .rotated_minos(Rotation::North)
-                .map(|p| p + meta.spawn_point),
+            minos: rotation_system
+                .minos(meta, Rotation::North)
+                .map(|p| p + spawn_point),
             lock_placements: 0,
-            y_min: meta.spawn_point.y,
+            y_min: spawn_point.y,
         }
     }
 
+    /// A tetromino in whichever rotation & position covers the minos, e.g. when imported
+    pub fn from_minos(
+        shape: TetrominoShape,
+        rotation_system: RotationSystem,
+        minos: Minos,
+    ) -> Option<Self> {
+        let sorted = |mut minos: Minos| {
+            minos.sort_by_key(|p| (p.x, p.y));
+            minos
+        };
+        let target = sorted(minos);
+        let meta = shape.meta();
+        [Rotation::North, Rotation::East, Rotation::South, Rotation::West]
+            .into_iter()
+            .find_map(|rotation| {
+                let rotated = rotation_system.minos(meta, rotation);
+                let position = target[0] - sorted(rotated)[0];
+                (sorted(rotated.map(|p| p + position)) == target).then_some(Self {
+                    meta: *meta,
+                    rotation_system,
+                    position,
+                    rotation,
+                    minos: rotated.map(|p| p + position),
+                    lock_placements: 0,
+                    y_min: position.y,
+                })
+            })
+    }
+
     pub fn shape(&self) -> TetrominoShape {
         self.meta.shape
     }
 
-    pub fn rotation(&self) -> Rotation {
-        self.rotation
+    /// The rotation to render the minos of this tetromino with
+    pub fn sprite_rotation(&self) -> Rotation {
+        self.rotation_system.sprite_rotation(self.meta.shape, self.rotation)
     }
 
     pub fn minos(&self) -> Minos {
@@ -440,19 +551,35 @@ impl Tetromino {
     }
 
     pub fn possible_minos_after_rotation(&self, clockwise: bool) -> Vec<Minos> {
-        let to_rotation = self.rotation.rotate(clockwise);
-        let basic_rotation_minos = self.meta.rotated_minos(to_rotation);
+        self.possible_minos_after_rotation_to(self.rotation.rotate(clockwise))
+    }
+
+    pub fn possible_minos_after_half_turn(&self) -> Vec<Minos> {
+        self.possible_minos_after_rotation_to(self.rotation.flip())
+    }
+
+    fn possible_minos_after_rotation_to(&self, to_rotation: Rotation) -> Vec<Minos> {
+        let basic_rotation_minos = self.rotation_system.minos(&self.meta, to_rotation);
         return self
-            .meta
-            .wall_kicks(self.rotation, to_rotation)
+            .rotation_system
+            .kicks(&self.meta, self.rotation, to_rotation)
             .iter()
             .map(|kick| basic_rotation_minos.map(|p| p + self.position + *kick))
             .collect::<Vec<Minos>>();
     }
 
     pub fn rotate(&mut self, clockwise: bool, wall_kick_id: usize) {
-        let to_rotation = self.rotation.rotate(clockwise);
-        let wall_kick = self.meta.wall_kicks(self.rotation, to_rotation)[wall_kick_id];
+        self.rotate_to(self.rotation.rotate(clockwise), wall_kick_id);
+    }
+
+    pub fn half_turn(&mut self, wall_kick_id: usize) {
+        self.rotate_to(self.rotation.flip(), wall_kick_id);
+    }
+
+    fn rotate_to(&mut self, to_rotation: Rotation, wall_kick_id: usize) {
+        let wall_kick = self
+            .rotation_system
+            .kicks(&self.meta, self.rotation, to_rotation)[wall_kick_id];
         self.rotation = to_rotation;
         self.translate_point(wall_kick);
     }
@@ -460,8 +587,8 @@ impl Tetromino {
     fn translate_point(&mut self, p: Point) {
         self.position += p;
         self.minos = self
-            .meta
-            .rotated_minos(self.rotation)
+            .rotation_system
+            .minos(&self.meta, self.rotation)
             .map(|p| p + self.position);
         if self.position.y < self.y_min {
             self.y_min = self.position.y;
@@ -502,7 +629,7 @@ mod tests {
 
     #[test]
     fn clockwise_rotation_with_wall_kicks() {
-        let tetromino = Tetromino::new(TetrominoShape::J);
+        let tetromino = Tetromino::new(TetrominoShape::J, RotationSystem::Srs);
         let observed = tetromino.possible_minos_after_rotation(true);
 
         assert_eq!(
@@ -542,9 +669,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn half_turn_kicks_j() {
+        assert_eq!(
+            TetrominoShape::J.meta().half_turn_kicks(Rotation::North),
+            [
+                Point::new(0, 0),
+                Point::new(0, 1),
+                Point::new(1, 1),
+                Point::new(-1, 1),
+                Point::new(1, 0),
+                Point::new(-1, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn half_turn_kicks_i() {
+        assert_eq!(
+            TetrominoShape::I.meta().half_turn_kicks(Rotation::North),
+            [Point::new(1, -1), Point::new(1, 0)]
+        );
+    }
+
+    #[test]
+    fn half_turn_kicks_o() {
+        assert_eq!(
+            TetrominoShape::O.meta().half_turn_kicks(Rotation::East),
+            [Point::new(1, -1)]
+        );
+    }
+
+    #[test]
+    fn half_turn_rotation_with_wall_kicks() {
+        let tetromino = Tetromino::new(TetrominoShape::J, RotationSystem::Srs);
+        let observed = tetromino.possible_minos_after_half_turn();
+
+        assert_eq!(
+            observed,
+            vec![
+                [
+                    Point::new(5, 20),
+                    Point::new(4, 20),
+                    Point::new(3, 20),
+                    Point::new(5, 19)
+                ],
+                [
+                    Point::new(5, 21),
+                    Point::new(4, 21),
+                    Point::new(3, 21),
+                    Point::new(5, 20)
+                ],
+                [
+                    Point::new(6, 21),
+                    Point::new(5, 21),
+                    Point::new(4, 21),
+                    Point::new(6, 20)
+                ],
+                [
+                    Point::new(4, 21),
+                    Point::new(3, 21),
+                    Point::new(2, 21),
+                    Point::new(4, 20)
+                ],
+                [
+                    Point::new(6, 20),
+                    Point::new(5, 20),
+                    Point::new(4, 20),
+                    Point::new(6, 19)
+                ],
+                [
+                    Point::new(4, 20),
+                    Point::new(3, 20),
+                    Point::new(2, 20),
+                    Point::new(4, 19)
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn half_turn() {
+        let mut tetromino = Tetromino::new(TetrominoShape::J, RotationSystem::Srs);
+        tetromino.half_turn(1);
+        assert_eq!(
+            tetromino.minos(),
+            [
+                Point::new(5, 21),
+                Point::new(4, 21),
+                Point::new(3, 21),
+                Point::new(5, 20)
+            ]
+        );
+    }
+
     #[test]
     fn minos() {
-        let tetromino = Tetromino::new(TetrominoShape::L);
+        let tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         assert_eq!(
             tetromino.minos(),
             [
@@ -558,7 +779,7 @@ mod tests {
 
     #[test]
     fn translate() {
-        let mut tetromino = Tetromino::new(TetrominoShape::L);
+        let mut tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         tetromino.translate(1, -1);
         assert_eq!(
             tetromino.minos(),
@@ -573,19 +794,19 @@ mod tests {
 
     #[test]
     fn lock_placements_initial() {
-        let tetromino = Tetromino::new(TetrominoShape::L);
+        let tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         assert_eq!(tetromino.lock_placements(), 0);
     }
 
     #[test]
     fn register_lock_placement() {
-        let mut tetromino = Tetromino::new(TetrominoShape::L);
+        let mut tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         assert_eq!(tetromino.register_lock_placement(), 1);
     }
 
     #[test]
     fn lock_placement_reset_on_translation_below_y_min() {
-        let mut tetromino = Tetromino::new(TetrominoShape::L);
+        let mut tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         tetromino.register_lock_placement();
         tetromino.translate(0, -1);
         assert_eq!(tetromino.lock_placements(), 0);
@@ -593,7 +814,7 @@ mod tests {
 
     #[test]
     fn lock_placement_not_reset_on_translation_above_y_min() {
-        let mut tetromino = Tetromino::new(TetrominoShape::L);
+        let mut tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         tetromino.translate(0, -2); // set y_min = -2
         tetromino.translate(0, 1); // translate above y_min
         tetromino.register_lock_placement();
@@ -603,7 +824,7 @@ mod tests {
 
     #[test]
     fn lock_placement_not_reset_on_x_translation() {
-        let mut tetromino = Tetromino::new(TetrominoShape::L);
+        let mut tetromino = Tetromino::new(TetrominoShape::L, RotationSystem::Srs);
         tetromino.register_lock_placement();
         tetromino.translate(1, 0);
         assert_eq!(tetromino.lock_placements(), 1);
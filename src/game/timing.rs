@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use strum::IntoEnumIterator;
+
+/// Duration of a number of frames at 60fps
+const fn frames(count: u64) -> Duration {
+    Duration::from_micros(count * 1_000_000 / 60)
+}
+
+// pre-calculated step durations in ms: 1000 * (0.8 - (level as f64 * 0.007)).powi(level as i32)
+// doing it like this as fp logic is not yet supported at compile time
+const GUIDELINE_GRAVITY: [u64; 15] = [
+    1000, 793, 618, 473, 355, 262, 190, 135, 94, 64, 43, 28, 18, 11, 7,
+];
+
+// master mode gravity from TGM as (level, rows per frame * 256)
+const MASTER_GRAVITY: [(u32, u64); 30] = [
+    (0, 4),
+    (30, 6),
+    (35, 8),
+    (40, 10),
+    (50, 12),
+    (60, 16),
+    (70, 32),
+    (80, 48),
+    (90, 64),
+    (100, 80),
+    (120, 96),
+    (140, 112),
+    (160, 128),
+    (170, 144),
+    (200, 4),
+    (220, 32),
+    (230, 64),
+    (233, 96),
+    (236, 128),
+    (239, 160),
+    (243, 192),
+    (247, 224),
+    (251, 256),
+    (300, 512),
+    (330, 768),
+    (360, 1024),
+    (400, 1280),
+    (420, 1024),
+    (450, 768),
+    (500, 5120),
+];
+
+/// Time to fall one row at a TGM internal gravity, 20G and above falls straight onto the stack
+const fn tgm_gravity(internal: u64) -> Duration {
+    if internal >= 20 * 256 {
+        Duration::ZERO
+    } else {
+        Duration::from_micros(256 * 1_000_000 / 60 / internal)
+    }
+}
+
+// frames per row on the NTSC NES, level 29 onwards is the kill screen
+const CLASSIC_GRAVITY: [u64; 30] = [
+    48, 43, 38, 33, 28, 23, 18, 13, 8, 6, 5, 5, 5, 4, 4, 4, 3, 3, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2,
+    1,
+];
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::IntoStaticStr,
+    strum::EnumIter,
+    strum::EnumString,
+)]
+pub enum TimingPreset {
+    /// Modern guideline timings with a move reset lock delay
+    #[default]
+    #[strum(serialize = "guideline")]
+    Guideline,
+    /// NES gravity curve with no lock delay
+    #[strum(serialize = "classic")]
+    Classic,
+    /// Pieces fall instantly to the stack with a fixed lock delay that is only reset by falling
+    #[strum(serialize = "20g")]
+    Tgm20G,
+}
+
+impl TimingPreset {
+    pub fn names() -> Vec<&'static str> {
+        Self::iter().map(|e| e.into()).collect()
+    }
+
+    pub fn timings(self) -> GameTimings {
+        match self {
+            TimingPreset::Guideline => GameTimings {
+                lock_delay: Duration::from_millis(500),
+                max_lock_placements: 15,
+                spawn_delay: Duration::from_millis(500),
+                spawn_delay_follows_gravity: true,
+                line_clear_delay: Duration::ZERO,
+                garbage_delay: Duration::from_millis(50),
+                gravity: GUIDELINE_GRAVITY
+                    .iter()
+                    .map(|ms| Duration::from_millis(*ms))
+                    .collect(),
+                sections: vec![],
+            },
+            TimingPreset::Classic => GameTimings {
+                lock_delay: Duration::ZERO,
+                max_lock_placements: 0,
+                spawn_delay: frames(10),
+                spawn_delay_follows_gravity: false,
+                line_clear_delay: frames(18),
+                garbage_delay: Duration::from_millis(50),
+                gravity: CLASSIC_GRAVITY.iter().map(|f| frames(*f)).collect(),
+                sections: vec![],
+            },
+            TimingPreset::Tgm20G => GameTimings {
+                lock_delay: frames(30),
+                max_lock_placements: 0,
+                spawn_delay: frames(30),
+                spawn_delay_follows_gravity: false,
+                line_clear_delay: frames(41),
+                garbage_delay: Duration::from_millis(50),
+                gravity: vec![Duration::ZERO],
+                sections: vec![],
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameTimings {
+    /// How long a tetromino can rest on the stack before it locks
+    pub lock_delay: Duration,
+    /// Moves & rotations that reset the lock delay, 0 for it to only be reset by falling
+    pub max_lock_placements: u32,
+    /// Entry delay between a tetromino locking and the next spawning
+    pub spawn_delay: Duration,
+    /// Shorten the entry delay to the current gravity step when that is faster
+    pub spawn_delay_follows_gravity: bool,
+    /// Extra wait after any line clear animation before the next tetromino spawns
+    pub line_clear_delay: Duration,
+    /// Wait between each line of incoming garbage
+    pub garbage_delay: Duration,
+    /// Time taken to fall one row at each level, the last entry applies to all higher levels.
+    /// A zero duration drops straight onto the stack (20G)
+    pub gravity: Vec<Duration>,
+    /// Overrides that apply from a level onwards, for when levels count far beyond the gravity table
+    #[serde(default)]
+    pub sections: Vec<TimingSection>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingSection {
+    pub level: u32,
+    pub gravity: Option<Duration>,
+    pub lock_delay: Option<Duration>,
+    pub spawn_delay: Option<Duration>,
+    pub line_clear_delay: Option<Duration>,
+}
+
+impl GameTimings {
+    /// Master mode, levels count up to 999 reaching 20G at 500, then the delays shrink every section
+    pub fn master() -> Self {
+        let gravity_sections = MASTER_GRAVITY.iter().map(|(level, internal)| TimingSection {
+            level: *level,
+            gravity: Some(tgm_gravity(*internal)),
+            ..Default::default()
+        });
+        let delay_sections = [
+            TimingSection {
+                level: 500,
+                line_clear_delay: Some(frames(25)),
+                ..Default::default()
+            },
+            TimingSection {
+                level: 600,
+                line_clear_delay: Some(frames(16)),
+                ..Default::default()
+            },
+            TimingSection {
+                level: 700,
+                spawn_delay: Some(frames(16)),
+                line_clear_delay: Some(frames(12)),
+                ..Default::default()
+            },
+            TimingSection {
+                level: 800,
+                spawn_delay: Some(frames(12)),
+                line_clear_delay: Some(frames(6)),
+                ..Default::default()
+            },
+            TimingSection {
+                level: 900,
+                lock_delay: Some(frames(17)),
+                ..Default::default()
+            },
+        ];
+
+        Self {
+            lock_delay: frames(30),
+            max_lock_placements: 0,
+            spawn_delay: frames(25),
+            spawn_delay_follows_gravity: false,
+            line_clear_delay: frames(40),
+            garbage_delay: Duration::from_millis(50),
+            gravity: vec![tgm_gravity(MASTER_GRAVITY[0].1)],
+            sections: gravity_sections.chain(delay_sections).collect(),
+        }
+    }
+
+    pub fn gravity(&self, level: u32) -> Duration {
+        self.section_value(level, |s| s.gravity).unwrap_or_else(|| {
+            self.gravity
+                .get(level as usize)
+                .or(self.gravity.last())
+                .copied()
+                .unwrap_or(Duration::ZERO)
+        })
+    }
+
+    pub fn lock_delay(&self, level: u32) -> Duration {
+        self.section_value(level, |s| s.lock_delay).unwrap_or(self.lock_delay)
+    }
+
+    pub fn spawn_delay(&self, level: u32) -> Duration {
+        self.section_value(level, |s| s.spawn_delay).unwrap_or(self.spawn_delay)
+    }
+
+    pub fn line_clear_delay(&self, level: u32) -> Duration {
+        self.section_value(level, |s| s.line_clear_delay).unwrap_or(self.line_clear_delay)
+    }
+
+    /// Value from the highest section at or below the level that sets it
+    fn section_value<F>(&self, level: u32, f: F) -> Option<Duration>
+    where
+        F: Fn(&TimingSection) -> Option<Duration>,
+    {
+        self.sections
+            .iter()
+            .filter(|s| s.level <= level)
+            .filter_map(|s| f(s).map(|value| (s.level, value)))
+            .max_by_key(|(section_level, _)| *section_level)
+            .map(|(_, value)| value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn guideline_gravity() {
+        let timings = TimingPreset::Guideline.timings();
+        assert_eq!(timings.gravity(0), Duration::from_millis(1000));
+        assert_eq!(timings.gravity(14), Duration::from_millis(7));
+    }
+
+    #[test]
+    fn gravity_after_last_level_is_last_entry() {
+        let timings = TimingPreset::Classic.timings();
+        assert_eq!(timings.gravity(29), frames(1));
+        assert_eq!(timings.gravity(100), frames(1));
+    }
+
+    #[test]
+    fn master_gravity_by_section() {
+        let timings = GameTimings::master();
+        assert_eq!(timings.gravity(0), timings.gravity(29));
+        assert!(timings.gravity(30) < timings.gravity(29));
+        assert_eq!(timings.gravity(251), frames(1));
+        assert_eq!(timings.gravity(499), tgm_gravity(768));
+        assert_eq!(timings.gravity(500), Duration::ZERO);
+        assert_eq!(timings.gravity(999), Duration::ZERO);
+    }
+
+    #[test]
+    fn master_delays_shrink_by_section() {
+        let timings = GameTimings::master();
+        assert_eq!(timings.lock_delay(899), frames(30));
+        assert_eq!(timings.lock_delay(900), frames(17));
+        assert_eq!(timings.spawn_delay(699), frames(25));
+        assert_eq!(timings.spawn_delay(700), frames(16));
+        assert_eq!(timings.spawn_delay(999), frames(12));
+        assert_eq!(timings.line_clear_delay(0), frames(40));
+        assert_eq!(timings.line_clear_delay(950), frames(6));
+    }
+
+    #[test]
+    fn tgm_is_20g_at_every_level() {
+        let timings = TimingPreset::Tgm20G.timings();
+        assert_eq!(timings.gravity(0), Duration::ZERO);
+        assert_eq!(timings.gravity(20), Duration::ZERO);
+    }
+}
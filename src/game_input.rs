@@ -15,11 +15,16 @@ pub enum GameInputKey {
     HardDrop { player: u32 },
     RotateClockwise { player: u32 },
     RotateAnticlockwise { player: u32 },
+    Rotate180 { player: u32 },
     Hold { player: u32 },
+    Undo { player: u32 },
     Pause,
     ReturnToMenu,
     Quit,
     NextTheme,
+    ExportBoard,
+    ImportBoard,
+    Restart,
 }
 
 #[derive(Hash, Clone, Copy, Debug, PartialEq, Eq)]
@@ -111,6 +116,16 @@ impl GameInputContext {
         result
     }
 
+    /// Forget held keys e.g. when their release was read by another screen
+    pub fn clear(&mut self) {
+        self.current.clear();
+    }
+
+    /// Keys that are currently held down
+    pub fn held(&self) -> Vec<GameInputKey> {
+        self.current.keys().copied().collect()
+    }
+
     fn map_from_sdl_event(&self, event: Event) -> MaybeKey {
         match event {
             Event::Quit { .. } => MaybeKey::Down(GameInputKey::Quit),
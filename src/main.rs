@@ -1,10 +1,13 @@
 #![windows_subsystem = "windows"]
 
 mod animation;
+mod capture;
 mod build_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
 mod config;
+mod countdown;
+mod debug_overlay;
 mod event;
 mod font;
 mod frame_rate;
@@ -16,17 +19,28 @@ mod menu_input;
 mod particles;
 mod paused;
 mod player;
+mod results;
+mod save;
 mod scale;
 mod theme;
 mod theme_context;
+mod tools;
 mod icon;
 
 extern crate sdl2;
 
 use crate::animation::game_over::GameOverAnimate;
 use crate::animation::hard_drop::HardDropAnimation;
-use crate::config::{Config, GameConfig, MatchRules, MatchThemes, VideoMode};
+use crate::capture::{save_screenshot, Capture};
+use crate::countdown::CountdownScreen;
+use crate::debug_overlay::{DebugOverlay, PhaseTimer, PhaseTimings};
+use crate::config::{config_path, Config, GameConfig, MatchRules, MatchThemes, VideoMode};
+use crate::font::FontType;
 use crate::event::{GameEvent, HighScoreEntryEvent};
+use crate::game::puzzle::{Puzzle, Puzzles};
+use crate::game::random::{RandomMode, Seed};
+use crate::game::rotation::RotationSystem;
+use crate::game::timing::TimingPreset;
 use crate::game_input::GameInputKey;
 use crate::high_score::render::HighScoreRender;
 use crate::high_score::table::HighScoreTable;
@@ -43,26 +57,33 @@ use crate::particles::prescribed::{
 use crate::particles::render::ParticleRender;
 use crate::particles::source::ParticleSource;
 use crate::particles::Particles;
-use crate::paused::PausedScreen;
+use crate::paused::{PauseMenuAction, PausedScreen};
+use crate::results::ResultsScreen;
+use crate::save::SaveState;
 use crate::theme::all::AllThemes;
+use crate::theme::sound::{CriticalMusic, MusicFade};
 
 use game_input::GameInputContext;
 use player::Match;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::image::{InitFlag as ImageInitFlag, Sdl2ImageContext};
 use sdl2::mixer::{InitFlag as MixerInitFlag, AUDIO_S16LSB, DEFAULT_CHANNELS};
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::WindowCanvas;
 use sdl2::sys::mixer::MIX_CHANNELS;
 use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::FullscreenType;
 
 use sdl2::{AudioSubsystem, EventPump, Sdl};
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::rc::Rc;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 use crate::menu::sound::MenuSound;
-use theme_context::{PlayerTextures, TextureMode, ThemeContext};
+use theme_context::{player_texture_refs, PlayerTextures, TextureMode, ThemeContext};
 use crate::icon::app_icon;
 
 #[cfg(not(feature = "retro_handheld"))]
@@ -71,34 +92,70 @@ const MAX_PLAYERS: u32 = 2;
 #[cfg(feature = "retro_handheld")]
 const MAX_PLAYERS: u32 = 1;
 
+const FUMEN_EXPORT_NAME: &str = "board";
+
 const MAX_PARTICLES_PER_PLAYER: usize = 100000;
 const MAX_BACKGROUND_PARTICLES: usize = 100000;
+const REDUCED_MOTION_MAX_PARTICLES_PER_PLAYER: usize = 500;
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(250);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum MainMenuAction {
     Start,
+    Continue,
     ViewHighScores,
+    /// The window changed size so the menu has to be rebuilt
+    Resized,
     Quit,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QuitMenuAction {
+    Resume,
+    Suspend,
+    ReturnToMenu,
+}
+
 enum PostGameAction {
     NewHighScore(NewHighScore),
     ReturnToMenu,
+    /// Restart with the same config & puzzle, the seed is kept if configured to
+    Restart {
+        seed: Option<Seed>,
+        puzzle: Option<Puzzle>,
+    },
     Quit,
 }
 
+enum MatchStart {
+    /// The puzzle picked from the puzzle menu for puzzle rules
+    New { puzzle: Option<Puzzle> },
+    /// Quick restart, skipping the menus with a countdown
+    Restart {
+        seed: Option<Seed>,
+        puzzle: Option<Puzzle>,
+    },
+    Resume(SaveState),
+}
+
 struct TetrisSdl {
     config: Config,
     _sdl: Sdl,
-    ttf: Sdl2TtfContext,
+    /// Shared so fonts that live as long as a screen don't borrow the rest of self
+    ttf: Rc<Sdl2TtfContext>,
     _image: Sdl2ImageContext,
     canvas: WindowCanvas,
     event_pump: EventPump,
     _audio: AudioSubsystem,
     particle_scale: particles::scale::Scale,
     menu_sound: MenuSound,
-    game_config: GameConfig
+    game_config: GameConfig,
+    /// When the window last changed size, until the layout is rebuilt
+    resized_at: Option<Instant>,
+    /// Toggled by its key, drawn over matches
+    debug_overlay: bool,
+    /// Set by the screenshot key until the next frame is presented
+    screenshot: bool,
 }
 
 impl TetrisSdl {
@@ -127,13 +184,15 @@ impl TetrisSdl {
 
         let mut window_builder = video.window(build_info::PKG_NAME, width, height);
         match config.video.mode {
+            VideoMode::Window { .. } => {
+                window_builder.resizable();
+            }
             VideoMode::FullScreen { .. } => {
                 window_builder.fullscreen();
             }
             VideoMode::FullScreenDesktop => {
                 window_builder.fullscreen_desktop();
             }
-            _ => {}
         };
 
         let mut window = window_builder
@@ -165,13 +224,13 @@ impl TetrisSdl {
         sdl2::mixer::open_audio(44_100, AUDIO_S16LSB, DEFAULT_CHANNELS, 512)?;
         let _mixer_context = sdl2::mixer::init(MixerInitFlag::OGG)?;
         sdl2::mixer::allocate_channels((MAX_PLAYERS * MIX_CHANNELS) as i32);
-        sdl2::mixer::Music::set_volume(config.audio.music_volume());
-        let menu_sound = MenuSound::new(config.audio)?;
+        config.audio.apply();
+        let menu_sound = MenuSound::new()?;
 
         Ok(Self {
             config,
             _sdl: sdl,
-            ttf,
+            ttf: Rc::new(ttf),
             _image: image,
             canvas,
             event_pump,
@@ -179,9 +238,93 @@ impl TetrisSdl {
             particle_scale: particles::scale::Scale::new((width, height)),
             menu_sound,
             game_config: Default::default(),
+            resized_at: None,
+            debug_overlay: false,
+            screenshot: false,
         })
     }
 
+    /// Polls the sdl events, toggling fullscreen & the debug overlay on their keys,
+    /// flagging screenshots and when the window resizes
+    fn poll_events(&mut self) -> Result<Vec<Event>, String> {
+        let events = self.event_pump.poll_iter().collect::<Vec<Event>>();
+        let fullscreen_key = self.config.input.fullscreen.map(|key| key.into());
+        let debug_overlay_key = self.config.input.debug_overlay.map(|key| key.into());
+        let screenshot_key = self.config.input.screenshot.map(|key| key.into());
+        for event in events.iter() {
+            match event {
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    repeat: false,
+                    ..
+                } if Some(*keycode) == fullscreen_key => self.toggle_fullscreen()?,
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    repeat: false,
+                    ..
+                } if Some(*keycode) == debug_overlay_key => {
+                    self.debug_overlay = !self.debug_overlay
+                }
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    repeat: false,
+                    ..
+                } if Some(*keycode) == screenshot_key => self.screenshot = true,
+                Event::Window {
+                    win_event: WindowEvent::SizeChanged(..),
+                    ..
+                } => self.resized_at = Some(Instant::now()),
+                _ => {}
+            }
+        }
+        Ok(events)
+    }
+
+    fn toggle_fullscreen(&mut self) -> Result<(), String> {
+        let window = self.canvas.window_mut();
+        let fullscreen = match window.fullscreen_state() {
+            FullscreenType::Off => FullscreenType::Desktop,
+            _ => FullscreenType::Off,
+        };
+        window.set_fullscreen(fullscreen)
+    }
+
+    /// Presents the frame, saving it first when a screenshot was asked for
+    fn present(&mut self) {
+        if self.screenshot {
+            self.screenshot = false;
+            // a failed screenshot is not worth stopping the game for
+            if let Err(error) = save_screenshot(&self.canvas) {
+                println!("Failed to save screenshot, {}", error);
+            }
+        }
+        self.canvas.present();
+    }
+
+    /// Limited to the configured frame rate
+    fn frame_rate(&self) -> FrameRate {
+        FrameRate::new(self.config.video.max_fps)
+    }
+
+    /// The window has settled on a new size, resizes in quick succession e.g. dragging
+    /// the window edge are waited out so everything is only rebuilt once
+    fn is_resized(&self) -> bool {
+        self.resized_at
+            .is_some_and(|resized_at| resized_at.elapsed() >= RESIZE_DEBOUNCE)
+    }
+
+    /// Rescale the particles for the current window size
+    fn clear_resized(
+        &mut self,
+        fg_particles: &mut ParticleRender,
+        bg_particles: &mut ParticleRender,
+    ) {
+        self.resized_at = None;
+        self.particle_scale = particles::scale::Scale::new(self.canvas.window().size());
+        fg_particles.set_scale(self.particle_scale);
+        bg_particles.set_scale(self.particle_scale);
+    }
+
     fn orbit_particle_source(&self) -> Box<dyn ParticleSource> {
         let (window_width, window_height) = self.canvas.window().size();
         prescribed_orbit(
@@ -206,11 +349,22 @@ impl TetrisSdl {
         )
     }
 
-    pub fn main_menu(&mut self, particles: &mut ParticleRender) -> Result<MainMenuAction, String> {
+    /// Theme packs are listed after the built in themes by name,
+    /// the message is shown under the title e.g. when a suspended game could not be continued
+    pub fn main_menu(
+        &mut self,
+        particles: &mut ParticleRender,
+        pack_names: &[String],
+        message: Option<String>,
+    ) -> Result<MainMenuAction, String> {
+        const CONTINUE: &str = "continue";
         const PLAYERS: &str = "players";
         const THEMES: &str = "themes";
         const MODE: &str = "mode";
         const LEVEL: &str = "level";
+        const RANDOM: &str = "random";
+        const ROTATION: &str = "rotation";
+        const TIMINGS: &str = "timings";
         const HIGH_SCORES: &str = "high scores";
         const START: &str = "start";
         const QUIT: &str = "quit";
@@ -218,23 +372,48 @@ impl TetrisSdl {
         let texture_creator = self.canvas.texture_creator();
         let inputs = MenuInputContext::new(self.config.input);
         let modes = MatchRules::DEFAULT_MODES;
+        let theme_names = MatchThemes::names()
+            .into_iter()
+            .map(|s| s.to_string())
+            .chain(pack_names.iter().cloned())
+            .collect::<Vec<String>>();
+        if self.game_config.themes.menu_index() >= theme_names.len() {
+            // the pack has been removed since it was selected
+            self.game_config.themes = MatchThemes::All;
+        }
 
         let mut menu_items = vec![
             MenuItem::select_list(
                 THEMES,
-                MatchThemes::names().into_iter().map(|s| s.to_string()).collect(),
-                self.game_config.themes as usize,
+                theme_names.clone(),
+                self.game_config.themes.menu_index(),
             ),
             MenuItem::select_list(
                 MODE,
                 modes.iter().map(|m| m.name()).collect(),
-                modes.iter().position(|&m| m == self.game_config.rules).unwrap()
+                // the selected puzzle is picked later so match modes by name
+                modes.iter().position(|m| m.name() == self.game_config.rules.name()).unwrap()
             ),
             MenuItem::select_list(
                 LEVEL,
                 (0..10).map(|i| i.to_string()).collect(),
                 self.game_config.level as usize,
             ),
+            MenuItem::select_list(
+                RANDOM,
+                RandomMode::names().into_iter().map(|s| s.to_string()).collect(),
+                self.config.game.random_mode as usize,
+            ),
+            MenuItem::select_list(
+                ROTATION,
+                RotationSystem::names().into_iter().map(|s| s.to_string()).collect(),
+                self.config.game.rotation_system as usize,
+            ),
+            MenuItem::select_list(
+                TIMINGS,
+                TimingPreset::names().into_iter().map(|s| s.to_string()).collect(),
+                self.config.game.timings as usize,
+            ),
             MenuItem::select(HIGH_SCORES),
             MenuItem::select(START),
             MenuItem::select(QUIT),
@@ -254,47 +433,73 @@ impl TetrisSdl {
             )
         }
 
+        if SaveState::exists() {
+            menu_items.insert(0, MenuItem::select(CONTINUE));
+        }
+
         let mut menu = Menu::new(
             menu_items,
             &mut self.canvas,
             &self.ttf,
             &texture_creator,
             build_info::PKG_NAME.to_uppercase(),
-            None
+            message
         )?;
 
         particles.clear();
         particles.add_source(self.tetromino_race_particle_source());
 
-        let mut frame_rate = FrameRate::new();
+        let mut frame_rate = self.frame_rate();
 
+        self.menu_sound.set_theme(self.game_config.themes)?;
         self.menu_sound.play_main_menu_music()?;
         loop {
-            let delta = frame_rate.update()?;
+            let delta = frame_rate.update();
 
-            for key in inputs.parse(self.event_pump.poll_iter()).into_iter() {
+            for key in inputs.parse(self.poll_events()?.into_iter()).into_iter() {
                 if key == MenuInputKey::Quit {
                     return Ok(MainMenuAction::Quit);
                 }
                 match menu.read_key(key) {
                     None => match key {
                         MenuInputKey::Start => {
-                            self.menu_sound.play_chime()?;
+                            self.play_main_menu_start()?;
                             return Ok(MainMenuAction::Start);
                         }
                         _ => {}
                     },
                     Some((name, action)) => match name {
+                        CONTINUE => return Ok(MainMenuAction::Continue),
                         PLAYERS => self.game_config.players = action.parse::<u32>().unwrap(),
-                        THEMES => self.game_config.themes = MatchThemes::from_str(action).unwrap(),
+                        THEMES => {
+                            let theme_index =
+                                theme_names.iter().position(|name| name == action).unwrap();
+                            self.game_config.themes = MatchThemes::from_menu_index(theme_index);
+                            if self.menu_sound.set_theme(self.game_config.themes)? {
+                                self.menu_sound.play_main_menu_music()?;
+                            }
+                        }
                         MODE => {
                             let mode_index =
                                 modes.iter().position(|&m| m.name() == action).unwrap();
                             self.game_config.rules = modes[mode_index];
                         }
                         LEVEL => self.game_config.level = action.parse::<u32>().unwrap(),
+                        RANDOM => {
+                            self.config.game.random_mode = RandomMode::from_str(action).unwrap()
+                        }
+                        ROTATION => {
+                            self.config.game.rotation_system =
+                                RotationSystem::from_str(action).unwrap()
+                        }
+                        TIMINGS => {
+                            self.config.game.timings = TimingPreset::from_str(action).unwrap()
+                        }
                         HIGH_SCORES => return Ok(MainMenuAction::ViewHighScores),
-                        START => return Ok(MainMenuAction::Start),
+                        START => {
+                            self.play_main_menu_start()?;
+                            return Ok(MainMenuAction::Start);
+                        }
                         QUIT => return Ok(MainMenuAction::Quit),
                         _ => {}
                     },
@@ -303,6 +508,11 @@ impl TetrisSdl {
                 self.menu_sound.play_chime()?;
             }
 
+            // the layout is rebuilt for the new window size by the caller
+            if self.is_resized() {
+                return Ok(MainMenuAction::Resized);
+            }
+
             self.canvas.set_draw_color(Color::BLACK);
             self.canvas.clear();
 
@@ -313,10 +523,213 @@ impl TetrisSdl {
             // menu
             menu.draw(&mut self.canvas)?;
 
-            self.canvas.present();
+            self.present();
+        }
+    }
+
+    /// The start jingle waits for the puzzle menu when a puzzle is still to be picked
+    fn play_main_menu_start(&self) -> Result<(), String> {
+        match self.game_config.rules {
+            MatchRules::Puzzle { .. } => self.menu_sound.play_chime(),
+            _ => self.menu_sound.play_start(),
         }
     }
 
+    /// The chosen puzzle with its index in the puzzle list, None to go back to the main menu
+    pub fn puzzle_menu(
+        &mut self,
+        particles: &mut ParticleRender,
+    ) -> Result<Option<(usize, Puzzle)>, String> {
+        const BACK: &str = "back";
+
+        let texture_creator = self.canvas.texture_creator();
+        let inputs = MenuInputContext::new(self.config.input);
+        let (mut puzzles, message) = match Puzzles::load() {
+            Ok(puzzles) => (puzzles.puzzles, None),
+            Err(error) => {
+                println!("Bad puzzles, {}, using the defaults", error);
+                let message = "puzzles could not be loaded".to_string();
+                (Puzzles::default().puzzles, Some(message))
+            }
+        };
+        let titles = puzzles
+            .iter()
+            .map(|p| p.title())
+            .collect::<Vec<String>>();
+
+        let mut menu_items = titles
+            .iter()
+            .map(|title| MenuItem::select(title))
+            .collect::<Vec<MenuItem>>();
+        menu_items.push(MenuItem::select(BACK));
+
+        let mut menu = Menu::new(
+            menu_items,
+            &mut self.canvas,
+            &self.ttf,
+            &texture_creator,
+            "PUZZLES".to_string(),
+            message
+        )?;
+
+        let mut frame_rate = self.frame_rate();
+
+        loop {
+            let delta = frame_rate.update();
+
+            for key in inputs.parse(self.poll_events()?.into_iter()).into_iter() {
+                if key == MenuInputKey::Quit {
+                    return Ok(None);
+                }
+                if let Some((name, _)) = menu.read_key(key) {
+                    let index = titles.iter().position(|title| title == name);
+                    if index.is_some() {
+                        self.menu_sound.play_start()?;
+                    } else {
+                        self.menu_sound.play_chime()?;
+                    }
+                    return Ok(index.map(|index| (index, puzzles.swap_remove(index))));
+                }
+                self.menu_sound.play_chime()?;
+            }
+
+            // the layout is rebuilt for the new window size by the caller
+            if self.is_resized() {
+                return Ok(None);
+            }
+
+            self.canvas.set_draw_color(Color::BLACK);
+            self.canvas.clear();
+
+            particles.update(delta);
+            particles.draw(&mut self.canvas)?;
+
+            menu.draw(&mut self.canvas)?;
+
+            self.present();
+        }
+    }
+
+    /// Offered when leaving a match that is not over, the match can be suspended to continue later
+    pub fn quit_menu(&mut self) -> Result<QuitMenuAction, String> {
+        const RESUME: &str = "resume";
+        const SUSPEND: &str = "suspend";
+        const QUIT: &str = "quit";
+
+        let texture_creator = self.canvas.texture_creator();
+        let inputs = MenuInputContext::new(self.config.input);
+        let mut menu = Menu::new(
+            vec![
+                MenuItem::select(RESUME),
+                MenuItem::select(SUSPEND),
+                MenuItem::select(QUIT),
+            ],
+            &mut self.canvas,
+            &self.ttf,
+            &texture_creator,
+            "QUIT".to_string(),
+            Some("suspend to continue later".to_string()),
+        )?;
+
+        let mut frame_rate = self.frame_rate();
+
+        loop {
+            frame_rate.update();
+
+            for key in inputs.parse(self.poll_events()?.into_iter()).into_iter() {
+                if key == MenuInputKey::Quit {
+                    return Ok(QuitMenuAction::Resume);
+                }
+                if let Some((name, _)) = menu.read_key(key) {
+                    self.menu_sound.play_chime()?;
+                    return Ok(match name {
+                        SUSPEND => QuitMenuAction::Suspend,
+                        QUIT => QuitMenuAction::ReturnToMenu,
+                        _ => QuitMenuAction::Resume,
+                    });
+                }
+                self.menu_sound.play_chime()?;
+            }
+
+            // the layout is rebuilt for the new window size by the caller
+            if self.is_resized() {
+                return Ok(QuitMenuAction::Resume);
+            }
+
+            self.canvas.set_draw_color(Color::BLACK);
+            self.canvas.clear();
+            menu.draw(&mut self.canvas)?;
+            self.present();
+        }
+    }
+
+    /// Volumes are applied as they change and saved to the config on the way out
+    pub fn audio_menu(&mut self) -> Result<(), String> {
+        const MUSIC: &str = "music";
+        const EFFECTS: &str = "effects";
+        const BACK: &str = "back";
+
+        let texture_creator = self.canvas.texture_creator();
+        let inputs = MenuInputContext::new(self.config.input);
+        let volumes = (0..=10).map(|i| format!("{}%", i * 10)).collect::<Vec<String>>();
+        let volume_index = |volume: f64| (volume * 10.0).round() as usize;
+        let mut menu = Menu::new(
+            vec![
+                MenuItem::select_list(
+                    MUSIC,
+                    volumes.clone(),
+                    volume_index(self.config.audio.music_volume),
+                ),
+                MenuItem::select_list(
+                    EFFECTS,
+                    volumes.clone(),
+                    volume_index(self.config.audio.effects_volume),
+                ),
+                MenuItem::select(BACK),
+            ],
+            &mut self.canvas,
+            &self.ttf,
+            &texture_creator,
+            "AUDIO".to_string(),
+            None,
+        )?;
+
+        let mut frame_rate = self.frame_rate();
+
+        'menu: loop {
+            frame_rate.update();
+
+            for key in inputs.parse(self.poll_events()?.into_iter()).into_iter() {
+                if key == MenuInputKey::Quit {
+                    break 'menu;
+                }
+                if let Some((name, action)) = menu.read_key(key) {
+                    let index = volumes.iter().position(|v| v == action).unwrap_or(0);
+                    let volume = index as f64 / 10.0;
+                    match name {
+                        MUSIC => self.config.audio.music_volume = volume,
+                        EFFECTS => self.config.audio.effects_volume = volume,
+                        _ => break 'menu,
+                    }
+                    self.config.audio.apply();
+                }
+                self.menu_sound.play_chime()?;
+            }
+
+            // the layout is rebuilt for the new window size by the caller
+            if self.is_resized() {
+                break 'menu;
+            }
+
+            self.canvas.set_draw_color(Color::BLACK);
+            self.canvas.clear();
+            menu.draw(&mut self.canvas)?;
+            self.present();
+        }
+        self.menu_sound.play_chime()?;
+        Config::save_audio(self.config.audio)
+    }
+
     pub fn view_high_score(&mut self, particles: &mut ParticleRender) -> Result<(), String> {
         let texture_creator = self.canvas.texture_creator();
         let inputs = MenuInputContext::new(self.config.input);
@@ -325,9 +738,10 @@ impl TetrisSdl {
             return Ok(());
         }
 
+        let ttf = Rc::clone(&self.ttf);
         let mut view = HighScoreRender::new(
             high_scores,
-            &self.ttf,
+            &ttf,
             &texture_creator,
             self.canvas.window().size(),
             None,
@@ -336,15 +750,15 @@ impl TetrisSdl {
         particles.clear();
         particles.add_source(self.fireworks_particle_source());
 
-        let mut frame_rate = FrameRate::new();
+        let mut frame_rate = self.frame_rate();
 
         self.menu_sound.play_high_score_music()?;
         'menu: loop {
-            let delta = frame_rate.update()?;
+            let delta = frame_rate.update();
 
-            let events = inputs.parse(self.event_pump.poll_iter());
-            if !events.is_empty() {
-                // any button press
+            let events = inputs.parse(self.poll_events()?.into_iter());
+            if !events.is_empty() || self.is_resized() {
+                // any button press or the window resized
                 break 'menu;
             }
             self.canvas.set_draw_color(Color::BLACK);
@@ -356,7 +770,7 @@ impl TetrisSdl {
 
             view.draw(&mut self.canvas)?;
 
-            self.canvas.present();
+            self.present();
         }
         Ok(())
     }
@@ -373,9 +787,10 @@ impl TetrisSdl {
             return Ok(());
         }
 
+        let ttf = Rc::clone(&self.ttf);
         let mut table = HighScoreRender::new(
             high_scores,
-            &self.ttf,
+            &ttf,
             &texture_creator,
             self.canvas.window().size(),
             Some(new_high_score),
@@ -384,13 +799,13 @@ impl TetrisSdl {
         particles.clear();
         particles.add_source(self.fireworks_particle_source());
 
-        let mut frame_rate = FrameRate::new();
+        let mut frame_rate = self.frame_rate();
 
         self.menu_sound.play_high_score_music()?;
         'menu: loop {
-            let delta = frame_rate.update()?;
+            let delta = frame_rate.update();
 
-            for key in inputs.parse(self.event_pump.poll_iter()) {
+            for key in inputs.parse(self.poll_events()?.into_iter()) {
                 let event = match key {
                     MenuInputKey::Up => table.up(),
                     MenuInputKey::Down => table.down(),
@@ -418,7 +833,7 @@ impl TetrisSdl {
 
             table.draw(&mut self.canvas)?;
 
-            self.canvas.present();
+            self.present();
         }
 
         if let Some(new_entry) = table.new_entry() {
@@ -432,52 +847,77 @@ impl TetrisSdl {
 
     pub fn game(
         &mut self,
-        all_themes: &AllThemes,
+        all_themes: &mut AllThemes,
         bg_particles: &mut ParticleRender,
         fg_particles: &mut ParticleRender,
+        start: MatchStart,
     ) -> Result<PostGameAction, String> {
         let texture_creator = self.canvas.texture_creator();
         let mut inputs = GameInputContext::new(self.config.input);
-        let mut fixture = Match::new(self.game_config, self.config);
-        let window_size = self.canvas.window().size();
-        let mut themes = ThemeContext::new(all_themes, &texture_creator, self.game_config, self.config, window_size)?;
-
-        let mut player_textures = (0..self.game_config.players)
-            .map(|_| {
-                PlayerTextures::new(
-                    &texture_creator,
-                    themes.max_background_size(),
-                    themes.max_board_size(),
-                )
-                .unwrap()
-            })
-            .collect::<Vec<PlayerTextures>>();
-
-        // push mut refs of all textures and their render modes into a single vector so we can render to texture in one loop
-        let mut texture_refs: Vec<(&mut Texture, TextureMode)> = vec![];
-        for (player_index, textures) in player_textures.iter_mut().enumerate() {
-            let player = player_index as u32 + 1;
-            texture_refs.push((
-                &mut textures.background,
-                TextureMode::PlayerBackground(player),
-            ));
-            texture_refs.push((&mut textures.board, TextureMode::PlayerBoard(player)));
-        }
+        let menu_inputs = MenuInputContext::new(self.config.input);
+        let mut fixture = match start {
+            MatchStart::New { puzzle } => Match::new(self.game_config, self.config, None, puzzle)?,
+            MatchStart::Restart { seed, puzzle } => {
+                Match::new(self.game_config, self.config, seed, puzzle)?.with_countdown()
+            }
+            MatchStart::Resume(save) => {
+                self.game_config = save.game_config;
+                Match::resume(save)?
+            }
+        };
+        let mut window_size = self.canvas.window().size();
+        let players = self.game_config.players;
+        all_themes.fit_modern(
+            &mut self.canvas,
+            &self.ttf,
+            self.config,
+            players,
+            window_size,
+        )?;
+        let mut themes = ThemeContext::new(
+            all_themes,
+            &texture_creator,
+            self.game_config,
+            self.config,
+            window_size,
+        )?;
+        let mut player_textures = PlayerTextures::for_players(&texture_creator, &themes, players)?;
+        let mut texture_refs = player_texture_refs(&mut player_textures);
 
         fg_particles.clear();
         bg_particles.clear();
-        bg_particles.add_source(self.orbit_particle_source());
+        if !self.config.accessibility.reduced_motion {
+            bg_particles.add_source(self.orbit_particle_source());
+        }
 
-        themes.theme().music().play(-1)?;
-        let paused_screen =
+        let mut critical_music = CriticalMusic::default();
+        let mut music_fade = MusicFade::default();
+        themes.theme().music(false).play(-1)?;
+        if fixture.state().is_paused() {
+            sdl2::mixer::Music::pause();
+        }
+        let mut paused_screen =
             PausedScreen::new(&mut self.canvas, &self.ttf, &texture_creator, window_size)?;
+        let mut countdown_screen =
+            CountdownScreen::new(&mut self.canvas, &self.ttf, &texture_creator, window_size)?;
+        let mut results_screen: Option<ResultsScreen> = None;
 
         let mut player_hard_drop_animations: HashMap<u32, HardDropAnimation> = HashMap::new();
         let mut max_level = 0;
-        let mut frame_rate = FrameRate::new();
+        let mut frame_rate = self.frame_rate();
+        let mut debug_overlay = DebugOverlay::new();
+        let ttf = Rc::clone(&self.ttf);
+        let mut debug_overlay_font = FontType::Mono.load(&ttf, window_size.1 / 48)?;
+        let mut capture = self.config.capture.every_nth_frame.and_then(|every_nth_frame| {
+            Capture::new(every_nth_frame)
+                .map_err(|error| println!("Failed to start capture, {}, skipping", error))
+                .ok()
+        });
 
         loop {
-            let delta = frame_rate.update()?;
+            let delta = frame_rate.update();
+            let mut phases = PhaseTimings::default();
+            let mut phase_timer = PhaseTimer::start();
 
             let mut to_emit_particles = vec![];
 
@@ -486,9 +926,60 @@ impl TetrisSdl {
                 fixture.set_hard_dropping(*hard_dropping_player);
             }
 
+            // while paused the same events also drive the pause menu
+            let sdl_events = self.poll_events()?;
+            if self.is_resized() {
+                // rebuild everything sized to the window in place so the match carries on as it was
+                self.clear_resized(fg_particles, bg_particles);
+                window_size = self.canvas.window().size();
+                let current_theme = themes.current_index();
+                drop(texture_refs);
+                drop(themes);
+                all_themes.fit_modern(
+                    &mut self.canvas,
+                    &self.ttf,
+                    self.config,
+                    players,
+                    window_size,
+                )?;
+                themes = ThemeContext::new(
+                    all_themes,
+                    &texture_creator,
+                    self.game_config,
+                    self.config,
+                    window_size,
+                )?
+                .with_current(current_theme);
+                player_textures = PlayerTextures::for_players(&texture_creator, &themes, players)?;
+                texture_refs = player_texture_refs(&mut player_textures);
+                paused_screen =
+                    PausedScreen::new(&mut self.canvas, &self.ttf, &texture_creator, window_size)?;
+                countdown_screen = CountdownScreen::new(
+                    &mut self.canvas,
+                    &self.ttf,
+                    &texture_creator,
+                    window_size,
+                )?;
+                if results_screen.is_some() {
+                    results_screen = Some(ResultsScreen::new(
+                        &mut self.canvas,
+                        &self.ttf,
+                        &texture_creator,
+                        window_size,
+                        &fixture,
+                    )?);
+                }
+                debug_overlay_font = FontType::Mono.load(&ttf, window_size.1 / 48)?;
+            }
+            let menu_keys = if fixture.state().is_paused() {
+                menu_inputs.parse(sdl_events.iter().cloned())
+            } else {
+                vec![]
+            };
+
             let mut any_key_pressed = false;
-            let events = inputs
-                .update(delta, self.event_pump.poll_iter())
+            let mut events = inputs
+                .update(delta, sdl_events.into_iter())
                 .into_iter()
                 .flat_map(|input| {
                     any_key_pressed = true;
@@ -507,7 +998,11 @@ impl TetrisSdl {
                         GameInputKey::RotateAnticlockwise { player } => {
                             fixture.mut_game(player, |g| g.rotate(false))
                         }
+                        GameInputKey::Rotate180 { player } => {
+                            fixture.mut_game(player, |g| g.half_turn())
+                        }
                         GameInputKey::Hold { player } => fixture.mut_game(player, |g| g.hold()),
+                        GameInputKey::Undo { player } => fixture.mut_game(player, |g| g.undo()),
                         GameInputKey::Pause => match fixture.state() {
                             MatchState::Normal | MatchState::Paused => fixture.toggle_paused(),
                             _ => None,
@@ -515,28 +1010,132 @@ impl TetrisSdl {
                         GameInputKey::Quit => Some(GameEvent::Quit),
                         GameInputKey::ReturnToMenu => Some(GameEvent::ReturnToMenu),
                         GameInputKey::NextTheme => Some(GameEvent::NextTheme),
+                        GameInputKey::ExportBoard if fixture.state().is_paused() => {
+                            Some(GameEvent::ExportBoard)
+                        }
+                        GameInputKey::ExportBoard => None,
+                        GameInputKey::ImportBoard if fixture.state().is_paused() => {
+                            Some(GameEvent::ImportBoard)
+                        }
+                        GameInputKey::ImportBoard => None,
+                        GameInputKey::Restart => Some(GameEvent::Restart),
                     }
                 })
                 .collect::<Vec<GameEvent>>();
 
+            // held rotate & hold keys are applied to the next tetromino as it spawns (IRS & IHS)
+            for input in inputs.held() {
+                match input {
+                    GameInputKey::RotateClockwise { player }
+                        if fixture.is_buffering_inputs(player) =>
+                    {
+                        fixture.mut_game(player, |g| g.rotate(true));
+                    }
+                    GameInputKey::RotateAnticlockwise { player }
+                        if fixture.is_buffering_inputs(player) =>
+                    {
+                        fixture.mut_game(player, |g| g.rotate(false));
+                    }
+                    GameInputKey::Rotate180 { player } if fixture.is_buffering_inputs(player) => {
+                        fixture.mut_game(player, |g| g.half_turn());
+                    }
+                    GameInputKey::Hold { player } if fixture.is_buffering_inputs(player) => {
+                        fixture.mut_game(player, |g| g.hold());
+                    }
+                    _ => {}
+                }
+            }
+
+            // a pause key press wins over the menu, which might share the key
+            let pause_toggled = events
+                .iter()
+                .any(|e| matches!(e, GameEvent::Paused | GameEvent::UnPaused));
+            for key in menu_keys.into_iter().filter(|_| !pause_toggled) {
+                let action = paused_screen.read_key(key);
+                self.menu_sound.play_chime()?;
+                match action {
+                    Some(PauseMenuAction::Resume) => events.extend(fixture.toggle_paused()),
+                    Some(PauseMenuAction::Restart) => events.push(GameEvent::Restart),
+                    Some(PauseMenuAction::NextTheme) => events.push(GameEvent::NextTheme),
+                    Some(PauseMenuAction::Audio) => {
+                        self.audio_menu()?;
+                        inputs.clear();
+                        frame_rate = self.frame_rate();
+                    }
+                    Some(PauseMenuAction::MainMenu) => events.push(GameEvent::ReturnToMenu),
+                    Some(PauseMenuAction::Quit) => events.push(GameEvent::Quit),
+                    None => continue,
+                }
+                break;
+            }
+
             for event in events.into_iter() {
                 match event {
                     GameEvent::Quit => return Ok(PostGameAction::Quit),
+                    GameEvent::Restart => {
+                        let seed = self.config.game.restart_same_seed.then(|| fixture.seed());
+                        let puzzle = fixture.puzzle().cloned();
+                        return Ok(PostGameAction::Restart { seed, puzzle });
+                    }
+                    GameEvent::ReturnToMenu if !fixture.state().is_game_over() => {
+                        if !fixture.state().is_paused() {
+                            fixture.toggle_paused();
+                            sdl2::mixer::Music::pause();
+                        }
+                        match self.quit_menu()? {
+                            QuitMenuAction::Resume => {
+                                inputs.clear();
+                                frame_rate = self.frame_rate();
+                            }
+                            QuitMenuAction::Suspend => {
+                                fixture.suspend(self.game_config).save()?;
+                                return Ok(PostGameAction::ReturnToMenu);
+                            }
+                            QuitMenuAction::ReturnToMenu => {
+                                return Ok(PostGameAction::ReturnToMenu)
+                            }
+                        }
+                    }
                     GameEvent::ReturnToMenu => return Ok(PostGameAction::ReturnToMenu), // even if high score?!
                     GameEvent::Paused => sdl2::mixer::Music::pause(),
                     GameEvent::UnPaused => sdl2::mixer::Music::resume(),
+                    GameEvent::ExportBoard => {
+                        // one fumen per player
+                        let path = config_path(FUMEN_EXPORT_NAME)?.with_extension("txt");
+                        let fumens = fixture
+                            .players
+                            .iter()
+                            .map(|p| p.game.fumen())
+                            .collect::<Vec<String>>();
+                        std::fs::write(path, fumens.join("\n")).map_err(|e| e.to_string())?;
+                    }
+                    GameEvent::ImportBoard => {
+                        // same format as the export so boards can be edited & played on
+                        let path = config_path(FUMEN_EXPORT_NAME)?.with_extension("txt");
+                        match std::fs::read_to_string(path) {
+                            Ok(fumens) => {
+                                for (player, fumen) in
+                                    fixture.players.iter_mut().zip(fumens.lines())
+                                {
+                                    if let Err(error) = player.game.import_fumen(fumen) {
+                                        println!("Bad fumen, {}, skipping", error);
+                                    }
+                                }
+                            }
+                            Err(error) => println!("Failed to read boards, {}, skipping", error),
+                        }
+                    }
                     GameEvent::NextTheme if !fixture.state().is_game_over() => {
                         themes.start_fade(&mut self.canvas)?;
                         themes.next();
 
                         // handle music
                         match fixture.state() {
-                            MatchState::Normal => {
-                                themes.theme().music().fade_in(-1, 1000)?;
-                            }
+                            MatchState::Normal | MatchState::Countdown(_) => music_fade.start()?,
                             MatchState::Paused => {
                                 // switch music but pause it immediately
-                                themes.theme().music().play(-1)?;
+                                music_fade.cancel();
+                                themes.theme().music(critical_music.is_critical()).play(-1)?;
                                 sdl2::mixer::Music::pause();
                             }
                             _ => {}
@@ -581,7 +1180,17 @@ impl TetrisSdl {
                         }
                     }
                     if game_over_done {
-                        if let Some(high_score) = maybe_high_score {
+                        if results_screen.is_none() && fixture.rules().has_results() {
+                            results_screen = Some(ResultsScreen::new(
+                                &mut self.canvas,
+                                &self.ttf,
+                                &texture_creator,
+                                window_size,
+                                &fixture,
+                            )?);
+                        } else if results_screen.is_some() && !any_key_pressed {
+                            // wait for a key press to dismiss the results
+                        } else if let Some(high_score) = maybe_high_score {
                             // start high score entry
                             return Ok(PostGameAction::NewHighScore(high_score));
                         } else if any_key_pressed {
@@ -589,7 +1198,10 @@ impl TetrisSdl {
                         }
                     }
                 }
+                MatchState::Countdown(_) => fixture.update_countdown(delta),
                 MatchState::Normal if !themes.is_fading() => {
+                    fixture.update_survival(delta);
+
                     let mut garbage: Vec<(u32, u32)> = vec![];
                     let mut new_game_over: Option<u32> = None;
                     let mut next_theme = false;
@@ -680,17 +1292,31 @@ impl TetrisSdl {
                             fixture.send_garbage(from_player, send_garbage_lines);
                         }
 
+                        // maybe switch to or from the critical music
+                        let critical_changed = critical_music.update(fixture.max_stack_height());
+
                         // maybe change the theme
                         if next_theme {
                             themes.start_fade(&mut self.canvas)?;
                             themes.next();
-                            themes.theme().music().fade_in(-1, 1000)?;
+                            music_fade.start()?;
+                        } else if critical_changed && themes.theme().has_critical_music() {
+                            music_fade.start()?;
                         }
                     }
                 }
                 _ => {}
             }
 
+            // fade in the next track once the last has faded out
+            if matches!(fixture.state(), MatchState::Normal | MatchState::Countdown(_))
+                && music_fade.update(delta)
+            {
+                themes.theme().music(critical_music.is_critical()).fade_in(-1, 1000)?;
+            }
+
+            phases.update = phase_timer.lap();
+
             // update particles
             if !fixture.state().is_paused() {
                 fg_particles.update(delta);
@@ -702,16 +1328,19 @@ impl TetrisSdl {
             for emit in to_emit_particles.into_iter() {
                 fg_particles.add_source(emit.into_source(&themes, &self.particle_scale));
             }
+            phases.particles = phase_timer.lap();
 
             // clear
             self.canvas
                 .set_draw_color(themes.theme().background_color());
             self.canvas.clear();
+            phases.render_to_texture = phase_timer.lap();
 
             // draw bg particles
             if themes.render_bg_particles() {
                 bg_particles.draw(&mut self.canvas)?;
             }
+            phases.particles += phase_timer.lap();
 
             // draw the game
             self.canvas
@@ -724,7 +1353,7 @@ impl TetrisSdl {
                             let player = fixture.player(*player_id);
                             themes
                                 .theme()
-                                .draw_background(texture_canvas, &player.game)
+                                .draw_background(texture_canvas, &player.game, themes.is_compact())
                                 .unwrap();
                         }
                         TextureMode::PlayerBoard(player_id)
@@ -752,9 +1381,11 @@ impl TetrisSdl {
                 .map(|p| p.next_impact_offset(delta))
                 .collect();
             themes.draw_current(&mut self.canvas, &mut texture_refs, delta, offsets)?;
+            phases.render_to_texture += phase_timer.lap();
 
             // fg particles
             fg_particles.draw(&mut self.canvas)?;
+            phases.particles += phase_timer.lap();
 
             let mut remove_hard_drop_animations: Vec<u32> = vec![];
             for (player_id, animation) in player_hard_drop_animations.iter_mut() {
@@ -764,32 +1395,127 @@ impl TetrisSdl {
             }
             for player_id in remove_hard_drop_animations {
                 player_hard_drop_animations.remove(&player_id);
-                fixture.player_mut(player_id).impact();
+                if !self.config.accessibility.reduced_motion {
+                    fixture.player_mut(player_id).impact();
+                }
+            }
+
+            match fixture.state() {
+                MatchState::Paused => paused_screen.draw(&mut self.canvas)?,
+                MatchState::Countdown(remaining) => {
+                    countdown_screen.draw(&mut self.canvas, remaining)?
+                }
+                _ => {}
+            }
+            if let Some(results_screen) = &results_screen {
+                results_screen.draw(&mut self.canvas)?;
+            }
+            phases.render_to_texture += phase_timer.lap();
+
+            // captured without the debug overlay
+            if let Some(Err(error)) = capture.as_mut().map(|c| c.frame(&self.canvas)) {
+                println!("Failed to capture frame, {}, stopping capture", error);
+                capture = None;
             }
+            if self.debug_overlay {
+                debug_overlay.draw(&mut self.canvas)?;
+            }
+            self.present();
+            phases.present = phase_timer.lap();
 
-            if fixture.state().is_paused() {
-                paused_screen.draw(&mut self.canvas)?;
+            if self.debug_overlay && debug_overlay.push(delta, phases) {
+                let particle_count = fg_particles.particle_count() + bg_particles.particle_count();
+                debug_overlay.refresh(&debug_overlay_font, &texture_creator, particle_count)?;
             }
+        }
+    }
+}
 
-            self.canvas.present();
+fn run(
+    rustris: &mut TetrisSdl,
+    all_themes: &mut AllThemes,
+    fg_particles: &mut ParticleRender,
+    bg_particles: &mut ParticleRender,
+) -> Result<(), String> {
+    let mut menu_message = None;
+    loop {
+        if rustris.is_resized() {
+            // menus are laid out as they open so only the particles need rescaling
+            rustris.clear_resized(fg_particles, bg_particles);
+        }
+        let mut start =
+            match rustris.main_menu(bg_particles, all_themes.pack_names(), menu_message.take())? {
+                MainMenuAction::Start => {
+                    let puzzle = if let MatchRules::Puzzle { .. } = rustris.game_config.rules {
+                        match rustris.puzzle_menu(bg_particles)? {
+                            Some((index, puzzle)) => {
+                                rustris.game_config.rules = MatchRules::Puzzle { index };
+                                Some(puzzle)
+                            }
+                            None => continue,
+                        }
+                    } else {
+                        None
+                    };
+                    MatchStart::New { puzzle }
+                }
+                MainMenuAction::Continue => match SaveState::take() {
+                    Ok(save) => MatchStart::Resume(save),
+                    Err(error) => {
+                        println!("Bad suspended game, {}, skipping", error);
+                        menu_message = Some("suspended game could not be loaded".to_string());
+                        continue;
+                    }
+                },
+                MainMenuAction::ViewHighScores => {
+                    rustris.view_high_score(bg_particles)?;
+                    continue;
+                }
+                MainMenuAction::Resized => continue,
+                MainMenuAction::Quit => return Ok(()),
+            };
+        loop {
+            match rustris.game(all_themes, fg_particles, bg_particles, start)? {
+                PostGameAction::NewHighScore(high_score) => {
+                    rustris.new_high_score(high_score, bg_particles)?
+                }
+                PostGameAction::ReturnToMenu => (),
+                PostGameAction::Restart { seed, puzzle } => {
+                    start = MatchStart::Restart { seed, puzzle };
+                    continue;
+                }
+                PostGameAction::Quit => return Ok(()),
+            }
+            break;
         }
     }
 }
 
 fn main() -> Result<(), String> {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if let Some(output) = tools::run(&args) {
+        println!("{}", output?);
+        return Ok(());
+    }
+
     let mut rustris = TetrisSdl::new()?;
     let texture_creator = rustris.canvas.texture_creator();
-    let (_, window_height) = rustris.canvas.window().size();
-    let all_themes = AllThemes::new(
+    let window_size = rustris.canvas.window().size();
+    let mut all_themes = AllThemes::new(
         &mut rustris.canvas,
         &texture_creator,
         &rustris.ttf,
         rustris.config,
-        window_height,
+        window_size,
     )?;
+    let max_particles_per_player = if rustris.config.accessibility.reduced_motion {
+        REDUCED_MOTION_MAX_PARTICLES_PER_PLAYER
+    } else {
+        MAX_PARTICLES_PER_PLAYER
+    };
     let mut fg_particles = ParticleRender::new(
         &mut rustris.canvas,
-        Particles::new(MAX_PARTICLES_PER_PLAYER * MAX_PLAYERS as usize),
+        Particles::new(max_particles_per_player * MAX_PLAYERS as usize),
         &texture_creator,
         rustris.particle_scale,
         vec![],
@@ -803,20 +1529,10 @@ fn main() -> Result<(), String> {
         all_themes.all(),
     )?;
 
-    loop {
-        match rustris.main_menu(&mut bg_particles)? {
-            MainMenuAction::Start => {
-                match rustris.game(&all_themes, &mut fg_particles, &mut bg_particles)? {
-                    PostGameAction::NewHighScore(high_score) => {
-                        rustris.new_high_score(high_score, &mut bg_particles)?
-                    }
-                    PostGameAction::ReturnToMenu => (),
-                    PostGameAction::Quit => return Ok(()),
-                }
-            }
-            MainMenuAction::ViewHighScores => rustris.view_high_score(&mut bg_particles)?,
-            MainMenuAction::Quit => break
-        }
-    }
-    Ok(())
+    run(
+        &mut rustris,
+        &mut all_themes,
+        &mut fg_particles,
+        &mut bg_particles,
+    )
 }
@@ -1,35 +1,93 @@
-use crate::config::AudioConfig;
+use crate::config::MatchThemes;
 use crate::theme::sound::{load_sound, play_sound};
+use crate::theme::{nes, snes};
 use sdl2::mixer::{Chunk, Music};
 
 const CHIME: &[u8] = include_bytes!("chime.ogg");
 const MAIN_MENU_MUSIC: &[u8] = include_bytes!("main-menu.ogg");
 const HIGH_SCORE_MUSIC: &[u8] = include_bytes!("high-score.ogg");
 
+/// Menu sounds that a theme can replace, anything missing falls back to the generic sounds
+#[derive(Default)]
+struct ThemeMenuSound {
+    chime: Option<Chunk>,
+    start: Option<Chunk>,
+    music: Option<Music<'static>>,
+}
+
+impl ThemeMenuSound {
+    fn new(theme: MatchThemes) -> Result<Self, String> {
+        Ok(match theme {
+            MatchThemes::Nes => Self {
+                chime: Some(load_sound(nes::MENU_SOUND)?),
+                start: Some(load_sound(nes::MENU_START_SOUND)?),
+                music: None,
+            },
+            MatchThemes::Snes => Self {
+                music: Some(Music::from_static_bytes(snes::MENU_MUSIC)?),
+                ..Self::default()
+            },
+            _ => Self::default(),
+        })
+    }
+}
+
 pub struct MenuSound {
     chime: Chunk,
     main_menu_music: Music<'static>,
     high_score_music: Music<'static>,
+    theme: MatchThemes,
+    theme_sound: ThemeMenuSound,
 }
 
 impl MenuSound {
-    pub fn new(config: AudioConfig) -> Result<Self, String> {
+    pub fn new() -> Result<Self, String> {
         Ok(Self {
-            chime: load_sound(CHIME, config)?,
+            chime: load_sound(CHIME)?,
             main_menu_music: Music::from_static_bytes(MAIN_MENU_MUSIC)?,
             high_score_music: Music::from_static_bytes(HIGH_SCORE_MUSIC)?,
+            theme: MatchThemes::All,
+            theme_sound: ThemeMenuSound::default(),
         })
     }
 
+    /// Switch to the menu sounds of the selected theme, returns true when the music changed
+    pub fn set_theme(&mut self, theme: MatchThemes) -> Result<bool, String> {
+        if theme == self.theme {
+            return Ok(false);
+        }
+        let theme_sound = ThemeMenuSound::new(theme)?;
+        let music_changed = theme_sound.music.is_some() || self.theme_sound.music.is_some();
+        self.theme = theme;
+        self.theme_sound = theme_sound;
+        Ok(music_changed)
+    }
+
     pub fn play_chime(&self) -> Result<(), String> {
-        play_sound(&self.chime)
+        play_sound(self.theme_sound.chime.as_ref().unwrap_or(&self.chime))
+    }
+
+    /// Played when leaving the menu to start a match
+    pub fn play_start(&self) -> Result<(), String> {
+        match self.theme_sound.start.as_ref() {
+            Some(start) => play_sound(start),
+            None => self.play_chime(),
+        }
     }
 
     pub fn play_main_menu_music(&self) -> Result<(), String> {
-        self.main_menu_music.play(-1)
+        self.theme_sound
+            .music
+            .as_ref()
+            .unwrap_or(&self.main_menu_music)
+            .play(-1)
     }
 
     pub fn play_high_score_music(&self) -> Result<(), String> {
-        self.high_score_music.play(-1)
+        self.theme_sound
+            .music
+            .as_ref()
+            .unwrap_or(&self.high_score_music)
+            .play(-1)
     }
 }
@@ -32,6 +32,10 @@ impl Particles {
         self.particles.iter().flat_map(|g| g.particles()).collect()
     }
 
+    pub fn particle_count(&self) -> usize {
+        self.particles.iter().map(|g| g.len()).sum()
+    }
+
     pub fn update(&mut self, delta: Duration) {
         let delta_time = delta.as_secs_f64();
         self.update_life(delta_time);
@@ -65,7 +69,7 @@ impl Particles {
     }
 
     fn emit_particles(&mut self, delta: Duration) {
-        let current_particles = self.particles.iter().map(|g| g.len()).sum::<usize>() as i32;
+        let current_particles = self.particle_count() as i32;
         let mut max_particles = self.max_particles as i32 - current_particles;
 
         let mut to_remove = vec![];
@@ -67,6 +67,11 @@ impl<'a> ParticleRender<'a> {
         self.particles.clear();
     }
 
+    /// Particles carry on where they are, rendered at the new scale e.g. after the window resizes
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.scale = scale;
+    }
+
     pub fn add_source(&mut self, source: Box<dyn ParticleSource>) {
         self.particles.sources.push(source);
     }
@@ -75,6 +80,10 @@ impl<'a> ParticleRender<'a> {
         self.particles.update(delta)
     }
 
+    pub fn particle_count(&self) -> usize {
+        self.particles.particle_count()
+    }
+
     pub fn draw(&mut self, canvas: &mut WindowCanvas) -> Result<(), String> {
         for particle in self.particles.particles() {
             let (r, g, b): (u8, u8, u8) = particle.color().into();
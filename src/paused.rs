@@ -1,13 +1,32 @@
-use crate::font::{FontTexture, FontType};
+use crate::menu::{Menu, MenuItem};
+use crate::menu_input::MenuInputKey;
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
 use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::WindowContext;
 use crate::theme::helper::TextureFactory;
 
+const RESUME: &str = "resume";
+const RESTART: &str = "restart";
+const NEXT_THEME: &str = "next theme";
+const AUDIO: &str = "audio";
+const MAIN_MENU: &str = "main menu";
+const QUIT: &str = "quit";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PauseMenuAction {
+    Resume,
+    /// Start a new match with the same settings
+    Restart,
+    NextTheme,
+    Audio,
+    MainMenu,
+    Quit,
+}
+
 pub struct PausedScreen<'a> {
-    texture: Texture<'a>,
+    background: Texture<'a>,
+    menu: Menu<'a>,
 }
 
 impl<'a> PausedScreen<'a> {
@@ -17,27 +36,52 @@ impl<'a> PausedScreen<'a> {
         texture_creator: &'a TextureCreator<WindowContext>,
         (window_width, window_height): (u32, u32),
     ) -> Result<Self, String> {
-        let font = FontType::Bold.load(ttf, window_width / 24)?;
-        let font_texture =
-            FontTexture::from_string(&font, texture_creator, "Paused", Color::BLACK)?;
-        let font_rect = Rect::from_center(
-            (window_width as i32 / 2, window_height as i32 / 2),
-            font_texture.width,
-            font_texture.height,
-        );
+        let menu = Menu::new(
+            vec![
+                MenuItem::select(RESUME),
+                MenuItem::select(RESTART),
+                MenuItem::select(NEXT_THEME),
+                MenuItem::select(AUDIO),
+                MenuItem::select(MAIN_MENU),
+                MenuItem::select(QUIT),
+            ],
+            canvas,
+            ttf,
+            texture_creator,
+            "PAUSED".to_string(),
+            None,
+        )?;
 
-        let mut texture = texture_creator.create_texture_target_blended(window_width, window_height)?;
+        let mut background =
+            texture_creator.create_texture_target_blended(window_width, window_height)?;
         canvas
-            .with_texture_canvas(&mut texture, |c| {
-                c.set_draw_color(Color::RGBA(0xff, 0xff, 0xff, 0xdd));
+            .with_texture_canvas(&mut background, |c| {
+                c.set_draw_color(Color::RGBA(0, 0, 0, 0xdd));
                 c.clear();
-                c.copy(&font_texture.texture, None, font_rect).unwrap();
             })
             .map_err(|e| e.to_string())?;
-        Ok(Self { texture })
+        Ok(Self { background, menu })
+    }
+
+    /// Quit is left to the game inputs, which return to the main menu or exit
+    pub fn read_key(&mut self, key: MenuInputKey) -> Option<PauseMenuAction> {
+        if key == MenuInputKey::Quit {
+            return None;
+        }
+        let (name, _) = self.menu.read_key(key)?;
+        match name {
+            RESUME => Some(PauseMenuAction::Resume),
+            RESTART => Some(PauseMenuAction::Restart),
+            NEXT_THEME => Some(PauseMenuAction::NextTheme),
+            AUDIO => Some(PauseMenuAction::Audio),
+            MAIN_MENU => Some(PauseMenuAction::MainMenu),
+            QUIT => Some(PauseMenuAction::Quit),
+            _ => None,
+        }
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) -> Result<(), String> {
-        canvas.copy(&self.texture, None, None)
+    pub fn draw(&mut self, canvas: &mut WindowCanvas) -> Result<(), String> {
+        canvas.copy(&self.background, None, None)?;
+        self.menu.draw(canvas)
     }
 }
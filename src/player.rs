@@ -5,16 +5,25 @@ use crate::animation::{TextureAnimate, TextureAnimation};
 use crate::config::{Config, GameConfig, MatchRules};
 use crate::event::GameEvent;
 use crate::game::board::{compact_destroy_lines, DestroyLines};
-use crate::game::random::RandomTetromino;
-use crate::game::{Game, GameMetrics};
+use crate::game::master::MASTER_MAX_LEVEL;
+use crate::game::puzzle::Puzzle;
+use crate::game::random::{RandomTetromino, Seed};
+use crate::game::rotation::RotationSystem;
+use crate::game::survival::SurvivalTimer;
+use crate::game::timing::GameTimings;
+use crate::game::{Game, GameMetrics, GameMode};
 use crate::high_score::table::HighScoreTable;
 use crate::high_score::NewHighScore;
+use crate::save::SaveState;
 
 use rand::Rng;
 
 use crate::particles::prescribed::{PlayerParticleTarget, PlayerTargetedParticles};
 use std::time::Duration;
 
+/// Ready & go countdown before a restarted match
+pub const COUNTDOWN: Duration = Duration::from_millis(1500);
+
 pub struct Player {
     pub player: u32,
     pub game: Game,
@@ -25,10 +34,31 @@ pub struct Player {
 }
 
 impl Player {
-    pub fn new(player: u32, random: RandomTetromino, level: u32) -> Self {
+    pub fn new(
+        player: u32,
+        random: RandomTetromino,
+        level: u32,
+        rotation_system: RotationSystem,
+        buffer_inputs: bool,
+        timings: GameTimings,
+        mode: GameMode,
+    ) -> Self {
+        let game = Game::new(
+            player,
+            level,
+            random,
+            rotation_system,
+            buffer_inputs,
+            timings,
+            mode,
+        );
+        Self::from_game(player, game)
+    }
+
+    pub fn from_game(player: u32, game: Game) -> Self {
         Self {
             player,
-            game: Game::new(player, level, random),
+            game,
             destroy_animation: None,
             game_over_animation: None,
             impact_animation: ImpactAnimation::new(),
@@ -109,6 +139,8 @@ impl Player {
 pub enum MatchState {
     Normal,
     Paused,
+    /// Waiting to start, the remaining duration of the countdown
+    Countdown(Duration),
     GameOver { high_score: Option<NewHighScore> },
 }
 
@@ -127,31 +159,130 @@ pub struct Match {
     high_scores: HighScoreTable,
     state: MatchState,
     rules: MatchRules,
+    survival: Option<SurvivalTimer>,
+    puzzle: Option<Puzzle>,
 }
 
 impl Match {
-    pub fn new(game_config: GameConfig, config: Config) -> Self {
+    /// None for a random seed, the puzzle is required for puzzle rules
+    pub fn new(
+        game_config: GameConfig,
+        config: Config,
+        seed: Option<Seed>,
+        puzzle: Option<Puzzle>,
+    ) -> Result<Self, String> {
         if game_config.players == 0 {
             panic!("must have at least one player")
         }
 
         let randoms = config.game.random_mode.build(
             game_config.players as usize,
-            config.game.min_garbage_per_hole,
+            config.game.garbage_mode,
+            seed,
         );
 
-        Self {
+        let puzzle = match game_config.rules {
+            MatchRules::Puzzle { .. } => Some(puzzle.ok_or("no puzzle selected")?),
+            _ => None,
+        };
+        let stack = puzzle.as_ref().map(|p| p.stack_rows()).transpose()?;
+
+        let mode = match (game_config.rules, puzzle.as_ref()) {
+            (MatchRules::Master, _) => GameMode::Master,
+            (MatchRules::Zen { gravity }, _) => GameMode::Zen { gravity },
+            (_, Some(puzzle)) => GameMode::Puzzle { goal: puzzle.goal },
+            _ => GameMode::Standard,
+        };
+        let (level, timings) = if mode == GameMode::Master {
+            (0, GameTimings::master())
+        } else {
+            (game_config.level, config.game.timings.timings())
+        };
+
+        Ok(Self {
             players: randoms
                 .into_iter()
                 .enumerate()
-                .map(|(pid, rand)| Player::new(pid as u32 + 1, rand, game_config.level))
+                .map(|(pid, rand)| {
+                    let rand = match puzzle.as_ref() {
+                        Some(puzzle) => RandomTetromino::from_sequence(&puzzle.pieces),
+                        None => rand,
+                    };
+                    let mut player = Player::new(
+                        pid as u32 + 1,
+                        rand,
+                        level,
+                        config.game.rotation_system,
+                        config.game.buffer_inputs,
+                        timings.clone(),
+                        mode,
+                    );
+                    if let MatchRules::Dig { lines } = game_config.rules {
+                        player.game.fill_garbage(lines);
+                    }
+                    if let Some(stack) = stack.as_ref() {
+                        player.game.preset_stack(stack);
+                    }
+                    player
+                })
                 .collect::<Vec<Player>>(),
-            high_scores: HighScoreTable::load().unwrap(),
+            high_scores: HighScoreTable::load()?,
             state: MatchState::Normal,
             rules: game_config.rules,
+            survival: (game_config.rules == MatchRules::Survival).then(SurvivalTimer::default),
+            puzzle,
+        })
+    }
+
+    /// Resume a suspended match, it is paused until the players are ready
+    pub fn resume(save: SaveState) -> Result<Self, String> {
+        Ok(Self {
+            players: save
+                .games
+                .into_iter()
+                .enumerate()
+                .map(|(pid, game)| Player::from_game(pid as u32 + 1, game))
+                .collect(),
+            high_scores: HighScoreTable::load()?,
+            state: MatchState::Paused,
+            rules: save.game_config.rules,
+            survival: save.survival,
+            puzzle: save.puzzle,
+        })
+    }
+
+    pub fn suspend(self, game_config: GameConfig) -> SaveState {
+        SaveState {
+            game_config,
+            games: self.players.into_iter().map(|p| p.game).collect(),
+            survival: self.survival,
+            puzzle: self.puzzle,
+        }
+    }
+
+    pub fn puzzle(&self) -> Option<&Puzzle> {
+        self.puzzle.as_ref()
+    }
+
+    pub fn with_countdown(mut self) -> Self {
+        self.state = MatchState::Countdown(COUNTDOWN);
+        self
+    }
+
+    pub fn update_countdown(&mut self, delta: Duration) {
+        if let MatchState::Countdown(remaining) = self.state {
+            self.state = match remaining.checked_sub(delta) {
+                Some(remaining) if !remaining.is_zero() => MatchState::Countdown(remaining),
+                _ => MatchState::Normal,
+            };
         }
     }
 
+    /// Every player shares the seed
+    pub fn seed(&self) -> Seed {
+        self.player(1).game.seed()
+    }
+
     pub fn unset_flags(&mut self) {
         for player in self.players.iter_mut() {
             player.game.set_soft_drop(false);
@@ -181,6 +312,10 @@ impl Match {
         self.state
     }
 
+    pub fn rules(&self) -> MatchRules {
+        self.rules
+    }
+
     pub fn check_for_winning_player(&self) -> Option<u32> {
         match self.rules {
             MatchRules::ScoreSprint {
@@ -203,6 +338,21 @@ impl Match {
                     None
                 }
             }
+            MatchRules::Dig { .. } => self
+                .players
+                .iter()
+                .find(|p| p.game.garbage_rows() == 0)
+                .map(|p| p.player),
+            MatchRules::Master => self
+                .players
+                .iter()
+                .find(|p| p.game.level() >= MASTER_MAX_LEVEL)
+                .map(|p| p.player),
+            MatchRules::Puzzle { .. } => self
+                .players
+                .iter()
+                .find(|p| p.game.is_puzzle_solved())
+                .map(|p| p.player),
             _ => None,
         }
     }
@@ -217,7 +367,10 @@ impl Match {
     pub fn set_game_over(&mut self, player: u32, animation_type: GameOverAnimationType) {
         let best_game = self.highest_score();
 
-        let high_score = if self.high_scores.is_high_score(best_game.score) {
+        // puzzles are not scored
+        let high_score = if self.puzzle.is_none()
+            && self.high_scores.is_high_score(best_game.score)
+        {
             Some(NewHighScore::new(best_game.player, best_game.score))
         } else {
             None
@@ -238,13 +391,20 @@ impl Match {
 
         match self.state {
             MatchState::Normal => match self.players.get_mut(player as usize - 1) {
-                Some(player) if !player.is_hard_dropping => f(&mut player.game),
+                // inputs during a hard drop animation are only let through to be buffered
+                Some(player) if !player.is_hard_dropping || player.game.is_buffering_inputs() => {
+                    f(&mut player.game)
+                }
                 _ => None,
             },
             _ => None,
         }
     }
 
+    pub fn is_buffering_inputs(&self, player: u32) -> bool {
+        self.state == MatchState::Normal && self.player(player).game.is_buffering_inputs()
+    }
+
     pub fn player(&self, player: u32) -> &Player {
         debug_assert!(player > 0);
         self.players.get(player as usize - 1).unwrap()
@@ -255,6 +415,20 @@ impl Match {
         self.players.get_mut(player as usize - 1).unwrap()
     }
 
+    /// Send any garbage that has risen on the survival timer to every player
+    pub fn update_survival(&mut self, delta: Duration) {
+        let rows = match self.survival.as_mut() {
+            Some(survival) => survival.update(delta),
+            None => return,
+        };
+        if rows == 0 {
+            return;
+        }
+        for player in self.players.iter_mut() {
+            player.game.send_garbage(rows);
+        }
+    }
+
     pub fn send_garbage(&mut self, from_player: u32, garbage_lines: u32) {
         debug_assert!(from_player > 0);
         if self.players.len() < 2 || !self.rules.garbage_enabled() {
@@ -280,6 +454,15 @@ impl Match {
             .send_garbage(garbage_lines);
     }
 
+    /// Height of the tallest stack of all players
+    pub fn max_stack_height(&self) -> u32 {
+        self.players
+            .iter()
+            .map(|p| p.game.stack_height())
+            .max()
+            .unwrap_or(0)
+    }
+
     fn highest_score(&self) -> GameMetrics {
         self.players
             .iter()
@@ -0,0 +1,121 @@
+use crate::config::MatchRules;
+use crate::font::{FontTexture, FontType};
+use crate::player::{Match, Player};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, TextureCreator, WindowCanvas};
+use sdl2::ttf::Sdl2TtfContext;
+use sdl2::video::WindowContext;
+use std::time::Duration;
+use crate::theme::helper::TextureFactory;
+
+const SECTION_LEVELS: usize = 100;
+
+fn format_time(duration: Duration) -> String {
+    let centis = duration.as_millis() / 10;
+    format!("{}:{:02}.{:02}", centis / 6000, (centis / 100) % 60, centis % 100)
+}
+
+/// Lines of text to show for a player, flagged if they are a heading
+fn player_results(rules: MatchRules, player: &Player) -> Vec<(bool, String)> {
+    let metrics = player.game.metrics();
+    let mut lines = vec![(true, format!("Player {}", player.player))];
+    match (rules, player.game.master()) {
+        (MatchRules::Master, Some(master)) => {
+            lines.push((true, format!("Level {}", metrics.level)));
+            for (section, time) in master.section_times().iter().enumerate() {
+                let text = format!("{:03} {}", section * SECTION_LEVELS, format_time(*time));
+                lines.push((false, text));
+            }
+            lines.push((true, format!("Total {}", format_time(master.total_time()))));
+        }
+        (MatchRules::Dig { lines: dig_lines }, _) => {
+            let cleared = dig_lines.saturating_sub(player.game.garbage_rows());
+            lines.push((false, format!("Cleared {}/{}", cleared, dig_lines)));
+            lines.push((false, format!("Pieces {}", metrics.pieces)));
+            lines.push((true, format!("Time {}", format_time(metrics.time))));
+        }
+        (MatchRules::Survival, _) => {
+            lines.push((false, format!("Lines {}", metrics.lines)));
+            lines.push((true, format!("Time {}", format_time(metrics.time))));
+        }
+        (MatchRules::Puzzle { .. }, _) => {
+            let status = if player.game.is_puzzle_solved() { "Solved" } else { "Failed" };
+            lines.push((true, status.to_string()));
+            lines.push((false, format!("Pieces {}", metrics.pieces)));
+            lines.push((false, format!("Time {}", format_time(metrics.time))));
+        }
+        _ => {}
+    }
+    lines
+}
+
+/// Results of each player at the end of a match, e.g. master section times
+pub struct ResultsScreen<'a> {
+    texture: Texture<'a>,
+}
+
+impl<'a> ResultsScreen<'a> {
+    pub fn new(
+        canvas: &mut WindowCanvas,
+        ttf: &Sdl2TtfContext,
+        texture_creator: &'a TextureCreator<WindowContext>,
+        (window_width, window_height): (u32, u32),
+        fixture: &Match,
+    ) -> Result<Self, String> {
+        let font_title = FontType::Bold.load(ttf, window_width / 32)?;
+        let font_body = FontType::Mono.load(ttf, window_width / 48)?;
+
+        let mut columns = vec![];
+        for player in fixture.players.iter() {
+            let mut lines = vec![];
+            for (heading, text) in player_results(fixture.rules(), player) {
+                let font = if heading { &font_title } else { &font_body };
+                lines.push(FontTexture::from_string(
+                    font,
+                    texture_creator,
+                    &text,
+                    Color::BLACK,
+                )?);
+            }
+            columns.push(lines);
+        }
+
+        let column_width = window_width / columns.len().max(1) as u32;
+        let mut texture =
+            texture_creator.create_texture_target_blended(window_width, window_height)?;
+        canvas
+            .with_texture_canvas(&mut texture, |c| {
+                c.set_draw_color(Color::RGBA(0xff, 0xff, 0xff, 0xdd));
+                c.clear();
+                for (index, lines) in columns.iter().enumerate() {
+                    let height: u32 = lines.iter().map(|line| line.height).sum();
+                    let center_x = (column_width * index as u32 + column_width / 2) as i32;
+                    let mut y = (window_height as i32 - height as i32) / 2;
+                    for line in lines.iter() {
+                        let x = center_x - line.width as i32 / 2;
+                        let rect = Rect::new(x, y, line.width, line.height);
+                        c.copy(&line.texture, None, rect).unwrap();
+                        y += line.height as i32;
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+        Ok(Self { texture })
+    }
+
+    pub fn draw(&self, canvas: &mut WindowCanvas) -> Result<(), String> {
+        canvas.copy(&self.texture, None, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_time() {
+        assert_eq!(format_time(Duration::from_millis(61_234)), "1:01.23");
+        assert_eq!(format_time(Duration::ZERO), "0:00.00");
+    }
+}
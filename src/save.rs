@@ -0,0 +1,43 @@
+use crate::config::{config_path, GameConfig};
+use crate::game::puzzle::Puzzle;
+use crate::game::survival::SurvivalTimer;
+use crate::game::Game;
+use serde::{Deserialize, Serialize};
+
+const CONFIG_NAME: &str = "suspended";
+
+/// An in-progress match suspended to disk to be continued later
+#[derive(Default, Serialize, Deserialize)]
+pub struct SaveState {
+    pub game_config: GameConfig,
+    pub games: Vec<Game>,
+    pub survival: Option<SurvivalTimer>,
+    pub puzzle: Option<Puzzle>,
+}
+
+impl SaveState {
+    pub fn exists() -> bool {
+        config_path(CONFIG_NAME).is_ok_and(|path| path.exists())
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let config_path = config_path(CONFIG_NAME)?;
+        confy::store_path(config_path, self).map_err(|e| e.to_string())
+    }
+
+    /// Load the suspended match, removing it so that it can only be continued once
+    pub fn take() -> Result<Self, String> {
+        let config_path = config_path(CONFIG_NAME)?;
+        #[cfg(debug_assertions)]
+        println!("loading suspended game: {}", config_path.to_str().unwrap());
+        let result: Result<Self, String> =
+            confy::load_path(&config_path).map_err(|e| e.to_string());
+        // removed even when unreadable so that a bad save does not stick around
+        std::fs::remove_file(config_path).map_err(|e| e.to_string())?;
+        let result = result?;
+        if result.games.is_empty() {
+            return Err("suspended game has no players".to_string());
+        }
+        Ok(result)
+    }
+}
@@ -3,6 +3,18 @@ use sdl2::rect::Rect;
 use crate::config::VideoConfig;
 use crate::theme::ThemeName;
 
+/// Fitted layouts narrower than this proportion of the window height are compact
+pub const COMPACT_RATIO: f64 = 2.0 / 3.0;
+
+/// How the window is split up between players
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerLayout {
+    /// Side by side
+    Horizontal,
+    /// Stacked top to bottom
+    Vertical,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Scale {
     players: u32,
@@ -10,7 +22,9 @@ pub struct Scale {
     window_width: u32,
     window_height: u32,
     block_size: f64,
-    integer_scale: Option<u32>
+    integer_scale: Option<u32>,
+    layout: PlayerLayout,
+    compact: bool,
 }
 
 impl Scale {
@@ -23,23 +37,32 @@ impl Scale {
         theme: ThemeName
     ) -> Self {
         let (window_width, window_height) = window_size;
-        let (bg_width, bg_height) = game_size;
 
         // the modern theme does it's own scaling
         let is_integer_scale = theme == ThemeName::Modern || config.integer_scale;
+        let padding_pct = config.screen_padding_pct();
 
-        let (scale, integer_scale) = if is_integer_scale {
-            let scale = min(window_width / (bg_width * players), window_height / bg_height);
-            (scale as f64, Some(scale))
+        // whichever way round fits the biggest game, preferring side by side
+        let [horizontal, vertical] = player_chunk_sizes(players, window_size);
+        let fit_horizontal = fit(game_size, horizontal, padding_pct, is_integer_scale);
+        let fit_vertical = fit(game_size, vertical, padding_pct, is_integer_scale);
+        let (layout, chunk_size, (scale, integer_scale)) = if fit_vertical.0 > fit_horizontal.0 {
+            (PlayerLayout::Vertical, vertical, fit_vertical)
         } else {
-            let padded_window_width = window_width as f64 - (2.0 * config.screen_padding_pct() * window_width as f64);
-            let scale_x = padded_window_width / (bg_width as f64 * players as f64);
-
-            let padded_window_height = window_height as f64 - (2.0 * config.screen_padding_pct() * window_height as f64);
-            let scale_y = padded_window_height / bg_height as f64;
+            (PlayerLayout::Horizontal, horizontal, fit_horizontal)
+        };
 
-            let scale = scale_x.min(scale_y);
-            (scale, None)
+        // a narrow chunk leaves the game small, so drop the padding to claw some size back
+        let (chunk_width, chunk_height) = chunk_size;
+        let (game_width, game_height) = game_size;
+        let width_scale = chunk_width as f64 / game_width as f64;
+        let height_scale = chunk_height as f64 / game_height as f64;
+        // the modern theme is laid out compact as it's rendered for the chunk
+        let compact = theme != ThemeName::Modern && width_scale < COMPACT_RATIO * height_scale;
+        let (scale, integer_scale) = if compact {
+            fit(game_size, chunk_size, 0.0, is_integer_scale)
+        } else {
+            (scale, integer_scale)
         };
 
         Self {
@@ -48,15 +71,31 @@ impl Scale {
             window_width,
             window_height,
             block_size: block_size as f64 * scale,
-            integer_scale
+            integer_scale,
+            layout,
+            compact,
         }
     }
 
-    /// splits the entire window up into horizontally stacked chunks equally between players
+    /// splits the entire window up into equal chunks between players, stacked by the layout
     pub fn player_window(&self, player: u32) -> Rect {
-        let player_chunk_width = self.window_width / self.players;
-        let x = player_chunk_width * (player - 1);
-        Rect::new(x as i32, 0, player_chunk_width, self.window_height)
+        match self.layout {
+            PlayerLayout::Horizontal => {
+                let player_chunk_width = self.window_width / self.players;
+                let x = player_chunk_width * (player - 1);
+                Rect::new(x as i32, 0, player_chunk_width, self.window_height)
+            }
+            PlayerLayout::Vertical => {
+                let player_chunk_height = self.window_height / self.players;
+                let y = player_chunk_height * (player - 1);
+                Rect::new(0, y as i32, self.window_width, player_chunk_height)
+            }
+        }
+    }
+
+    /// Compact layouts are for narrow windows, they show less of the peek queue
+    pub fn is_compact(&self) -> bool {
+        self.compact
     }
 
     pub fn scale_and_offset_rect(&self, rect: Rect, offset_x: i32, offset_y: i32) -> Rect {
@@ -103,3 +142,102 @@ impl Scale {
         )
     }
 }
+
+/// Each player's chunk of the window when side by side then when stacked
+pub fn player_chunk_sizes(players: u32, window_size: (u32, u32)) -> [(u32, u32); 2] {
+    let (window_width, window_height) = window_size;
+    [
+        (window_width / players, window_height),
+        (window_width, window_height / players),
+    ]
+}
+
+/// The scale that fits the game in a chunk of the window, integer scaled if requested
+fn fit(
+    game_size: (u32, u32),
+    chunk_size: (u32, u32),
+    padding_pct: f64,
+    is_integer_scale: bool,
+) -> (f64, Option<u32>) {
+    let (game_width, game_height) = game_size;
+    let (chunk_width, chunk_height) = chunk_size;
+    if is_integer_scale {
+        let scale = min(chunk_width / game_width, chunk_height / game_height);
+        (scale as f64, Some(scale))
+    } else {
+        let padded_chunk_width = chunk_width as f64 * (1.0 - 2.0 * padding_pct);
+        let padded_chunk_height = chunk_height as f64 * (1.0 - 2.0 * padding_pct);
+        let scale_x = padded_chunk_width / game_width as f64;
+        let scale_y = padded_chunk_height / game_height as f64;
+        (scale_x.min(scale_y), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VideoMode;
+
+    const GAME_SIZE: (u32, u32) = (200, 167);
+
+    fn video_config(integer_scale: bool) -> VideoConfig {
+        VideoConfig {
+            mode: VideoMode::FullScreenDesktop,
+            vsync: true,
+            disable_screensaver: true,
+            integer_scale,
+            max_fps: None,
+        }
+    }
+
+    fn scale(players: u32, window_size: (u32, u32)) -> Scale {
+        Scale::new(players, GAME_SIZE, window_size, 8, video_config(true), ThemeName::GameBoy)
+    }
+
+    #[test]
+    fn splits_wide_windows_side_by_side() {
+        let scale = scale(2, (1920, 1080));
+        assert_eq!(scale.layout, PlayerLayout::Horizontal);
+        assert!(!scale.is_compact());
+        assert_eq!(scale.player_window(2), Rect::new(960, 0, 960, 1080));
+    }
+
+    #[test]
+    fn stacks_portrait_windows() {
+        let scale = scale(2, (1080, 1920));
+        assert_eq!(scale.layout, PlayerLayout::Vertical);
+        assert_eq!(scale.player_window(2), Rect::new(0, 960, 1080, 960));
+    }
+
+    #[test]
+    fn compacts_narrow_windows() {
+        assert!(scale(2, (640, 480)).is_compact());
+        assert!(scale(1, (720, 1280)).is_compact());
+        assert!(!scale(1, (640, 480)).is_compact());
+    }
+
+    #[test]
+    fn leaves_the_modern_theme_to_lay_itself_out_compact() {
+        let config = video_config(false);
+        let modern = Scale::new(1, GAME_SIZE, (400, 720), 8, config, ThemeName::Modern);
+        assert!(!modern.is_compact());
+    }
+
+    #[test]
+    fn chunks_the_window_both_ways_round() {
+        assert_eq!(
+            player_chunk_sizes(2, (1920, 1080)),
+            [(960, 1080), (1920, 540)]
+        );
+    }
+
+    #[test]
+    fn compact_drops_the_padding() {
+        let config = video_config(false);
+        let padded = Scale::new(1, GAME_SIZE, (1280, 720), 8, config, ThemeName::Nes);
+        let compact = Scale::new(1, GAME_SIZE, (400, 720), 8, config, ThemeName::Nes);
+        assert!(!padded.is_compact());
+        assert!(compact.is_compact());
+        assert_eq!(compact.scale_length(GAME_SIZE.0), 400);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::config::MinoPalette;
+use crate::game::tetromino::TetrominoShape;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::WindowCanvas;
+
+const GLYPH_COLOR: Color = Color::RGBA(0, 0, 0, 0xb0);
+
+impl MinoPalette {
+    pub fn mino_color(self, shape: TetrominoShape) -> Option<Color> {
+        match self {
+            MinoPalette::Theme => None,
+            MinoPalette::ColorBlind => Some(match shape {
+                TetrominoShape::I => Color::RGB(0x56, 0xb4, 0xe9),
+                TetrominoShape::O => Color::RGB(0xf0, 0xe4, 0x42),
+                TetrominoShape::T => Color::RGB(0xcc, 0x79, 0xa7),
+                TetrominoShape::S => Color::RGB(0x00, 0x9e, 0x73),
+                TetrominoShape::Z => Color::RGB(0xd5, 0x5e, 0x00),
+                TetrominoShape::J => Color::RGB(0x00, 0x72, 0xb2),
+                TetrominoShape::L => Color::RGB(0xe6, 0x9f, 0x00),
+            }),
+            MinoPalette::HighContrast => Some(match shape {
+                TetrominoShape::I => Color::RGB(0x00, 0xff, 0xff),
+                TetrominoShape::O => Color::RGB(0xff, 0xff, 0x00),
+                TetrominoShape::T => Color::RGB(0xff, 0x00, 0xff),
+                TetrominoShape::S => Color::RGB(0xff, 0xff, 0xff),
+                TetrominoShape::Z => Color::RGB(0xff, 0x30, 0x30),
+                TetrominoShape::J => Color::RGB(0x30, 0x50, 0xff),
+                TetrominoShape::L => Color::RGB(0xff, 0xa0, 0x00),
+            }),
+        }
+    }
+
+    /// Replaces the particle colour of themes that emit particles
+    pub fn particle_color(self) -> Option<Color> {
+        match self {
+            MinoPalette::Theme => None,
+            MinoPalette::ColorBlind => Some(Color::RGB(0xf0, 0xe4, 0x42)),
+            MinoPalette::HighContrast => Some(Color::WHITE),
+        }
+    }
+
+    /// How much of the sprite shading survives recolouring, 0 for flat colours
+    pub fn shading(self) -> u8 {
+        match self {
+            MinoPalette::HighContrast => 0,
+            _ => 0x60,
+        }
+    }
+}
+
+/// Draws a glyph of the shape over a single mino, S/Z & J/L are mirrored
+pub fn draw_glyph(
+    canvas: &mut WindowCanvas,
+    shape: TetrominoShape,
+    mino: Rect,
+) -> Result<(), String> {
+    let size = mino.width() as i32;
+    let thickness = (size / 8).max(1);
+    let inset = size / 4;
+    let left = mino.x() + inset;
+    let top = mino.y() + inset;
+    let right = mino.right() - inset - thickness;
+    let bottom = mino.bottom() - inset - thickness;
+    let length = (right - left + thickness) as u32;
+    let center = mino.x() + (size - thickness) / 2;
+
+    let horizontal = |y: i32| Rect::new(left, y, length, thickness as u32);
+    let vertical = |x: i32| Rect::new(x, top, thickness as u32, length);
+
+    canvas.set_draw_color(GLYPH_COLOR);
+    match shape {
+        TetrominoShape::I => canvas.fill_rect(vertical(center)),
+        TetrominoShape::O => canvas.fill_rects(&[
+            horizontal(top),
+            horizontal(bottom),
+            vertical(left),
+            vertical(right),
+        ]),
+        TetrominoShape::T => canvas.fill_rects(&[horizontal(top), vertical(center)]),
+        TetrominoShape::J => canvas.fill_rects(&[horizontal(bottom), vertical(right)]),
+        TetrominoShape::L => canvas.fill_rects(&[horizontal(bottom), vertical(left)]),
+        TetrominoShape::S => draw_diagonal(canvas, (left, bottom), (right, top), thickness),
+        TetrominoShape::Z => draw_diagonal(canvas, (left, top), (right, bottom), thickness),
+    }
+}
+
+fn draw_diagonal(
+    canvas: &mut WindowCanvas,
+    (x1, y1): (i32, i32),
+    (x2, y2): (i32, i32),
+    thickness: i32,
+) -> Result<(), String> {
+    for offset in 0..thickness {
+        canvas.draw_line(Point::new(x1 + offset, y1), Point::new(x2 + offset, y2))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn palettes_have_distinct_colors() {
+        for palette in [MinoPalette::ColorBlind, MinoPalette::HighContrast] {
+            let colors = TetrominoShape::ALL
+                .iter()
+                .map(|shape| palette.mino_color(*shape).unwrap().rgba())
+                .collect::<HashSet<_>>();
+            assert_eq!(colors.len(), TetrominoShape::ALL.len());
+        }
+        assert_eq!(MinoPalette::Theme.mino_color(TetrominoShape::S), None);
+    }
+}
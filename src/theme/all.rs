@@ -1,7 +1,10 @@
 use crate::config::Config;
+use crate::scale::player_chunk_sizes;
 use crate::theme::gb::game_boy_theme;
+use crate::theme::gbc::game_boy_color_theme;
 use crate::theme::modern::modern_theme;
 use crate::theme::nes::nes_theme;
+use crate::theme::pack::theme_packs;
 use crate::theme::snes::snes_theme;
 use crate::theme::Theme;
 use sdl2::render::{TextureCreator, WindowCanvas};
@@ -9,10 +12,14 @@ use sdl2::ttf::Sdl2TtfContext;
 use sdl2::video::WindowContext;
 
 pub struct AllThemes<'a> {
+    texture_creator: &'a TextureCreator<WindowContext>,
     game_boy: Theme<'a>,
+    game_boy_color: Theme<'a>,
     nes: Theme<'a>,
     snes: Theme<'a>,
     modern: Theme<'a>,
+    packs: Vec<Theme<'a>>,
+    pack_names: Vec<String>,
 }
 
 impl<'a> AllThemes<'a> {
@@ -21,21 +28,84 @@ impl<'a> AllThemes<'a> {
         texture_creator: &'a TextureCreator<WindowContext>,
         ttf: &Sdl2TtfContext,
         config: Config,
-        window_height: u32,
+        window_size: (u32, u32),
     ) -> Result<Self, String> {
-        let game_boy = game_boy_theme(canvas, texture_creator, config)?;
-        let nes = nes_theme(canvas, texture_creator, config)?;
-        let snes = snes_theme(canvas, texture_creator, config)?;
-        let modern = modern_theme(canvas, texture_creator, ttf, config, window_height)?;
-        Ok(Self {
+        let game_boy = game_boy_theme(canvas, texture_creator)?;
+        let game_boy_color = game_boy_color_theme(canvas, texture_creator)?;
+        let nes = nes_theme(canvas, texture_creator)?;
+        let snes = snes_theme(canvas, texture_creator)?;
+        let modern = modern_theme(canvas, texture_creator, ttf, config, window_size)?;
+        let (pack_names, packs) = theme_packs(canvas, texture_creator)?.into_iter().unzip();
+        let mut result = Self {
+            texture_creator,
             game_boy,
+            game_boy_color,
             nes,
             snes,
             modern,
-        })
+            packs,
+            pack_names,
+        };
+        for theme in result.all_mut() {
+            theme.apply_accessibility(canvas, config.accessibility)?;
+        }
+        Ok(result)
+    }
+
+    /// Renders the modern theme again for each player's chunk of the window,
+    /// keeping whichever layout gives the biggest blocks
+    pub fn fit_modern(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        ttf: &Sdl2TtfContext,
+        config: Config,
+        players: u32,
+        window_size: (u32, u32),
+    ) -> Result<(), String> {
+        let mut modern: Option<Theme<'a>> = None;
+        for chunk_size in player_chunk_sizes(players, window_size) {
+            let theme = modern_theme(canvas, self.texture_creator, ttf, config, chunk_size)?;
+            let block_size = theme.geometry().block_size();
+            if modern
+                .as_ref()
+                .is_none_or(|m| block_size > m.geometry().block_size())
+            {
+                modern = Some(theme);
+            }
+        }
+        let mut modern = modern.unwrap();
+        // keep the loaded sound so music playing from the old layout carries on
+        std::mem::swap(&mut modern.sound, &mut self.modern.sound);
+        self.modern = modern;
+        self.modern
+            .apply_accessibility(canvas, config.accessibility)
+    }
+
+    fn all_mut(&mut self) -> Vec<&mut Theme<'a>> {
+        let mut result = vec![
+            &mut self.game_boy,
+            &mut self.game_boy_color,
+            &mut self.nes,
+            &mut self.snes,
+            &mut self.modern,
+        ];
+        result.extend(self.packs.iter_mut());
+        result
     }
 
     pub fn all(&self) -> Vec<&Theme<'a>> {
-        vec![&self.game_boy, &self.nes, &self.snes, &self.modern]
+        let mut result = vec![
+            &self.game_boy,
+            &self.game_boy_color,
+            &self.nes,
+            &self.snes,
+            &self.modern,
+        ];
+        result.extend(self.packs.iter());
+        result
+    }
+
+    pub fn pack_names(&self) -> &[String] {
+        &self.pack_names
     }
 }
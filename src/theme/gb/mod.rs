@@ -1,6 +1,5 @@
 use crate::animation::destroy::DestroyAnimationType;
 use crate::animation::game_over::GameOverAnimationType;
-use crate::config::Config;
 use crate::theme::retro::{retro_theme, RetroThemeOptions};
 use std::convert::TryInto;
 use std::iter::Iterator;
@@ -42,15 +41,34 @@ fn char_snip(row: i32, col: i32) -> Point {
     Point::new(1 + col * 8, 45 + row * 7)
 }
 
-pub fn game_boy_theme<'a>(
-    canvas: &mut WindowCanvas,
-    texture_creator: &'a TextureCreator<WindowContext>,
-    config: Config,
-) -> Result<Theme<'a>, String> {
-    let options = RetroThemeOptions::new(
-        ThemeName::GameBoy,
+/// Images & sounds of a theme laid out like the Game Boy, shared with the Game Boy Color
+pub struct GameBoyAssets {
+    pub sprites: &'static [u8],
+    pub background_file: &'static [u8],
+    pub board_file: &'static [u8],
+    pub game_over_file: &'static [u8],
+    pub music: &'static [u8],
+    pub move_sound: &'static [u8],
+    pub rotate_sound: &'static [u8],
+    pub lock_sound: &'static [u8],
+    pub send_garbage_sound: &'static [u8],
+    pub clear_sound: &'static [u8],
+    pub tetris_sound: &'static [u8],
+    pub level_up_sound: &'static [u8],
+    pub game_over_sound: &'static [u8],
+    pub pause_sound: &'static [u8],
+    pub victory_sound: &'static [u8],
+    pub stack_drop_sound: &'static [u8],
+    pub background_color: Color,
+    pub destroy_animation: DestroyAnimationType,
+    pub game_over_animation: GameOverAnimationType,
+}
+
+pub fn game_boy_options(name: ThemeName, assets: GameBoyAssets) -> RetroThemeOptions {
+    RetroThemeOptions::new(
+        name,
         TetrominoSpriteSheetMeta::new(
-            SPRITES,
+            assets.sprites,
             BLOCK_PIXELS,
             [
                 Point::new(1, 35),
@@ -67,9 +85,9 @@ pub fn game_boy_theme<'a>(
             (34, 35),
             0x30,
         ),
-        BACKGROUND_FILE,
-        BOARD_FILE,
-        GAME_OVER_FILE,
+        assets.background_file,
+        assets.board_file,
+        assets.game_over_file,
         [
             Rect::new(162, 11 + BUFFER_PIXELS as i32, 32, 32),
             Rect::new(162, 48 + BUFFER_PIXELS as i32, 32, 32),
@@ -79,7 +97,7 @@ pub fn game_boy_theme<'a>(
         ],
         Rect::new(12, 101 + BUFFER_PIXELS as i32, 32, 32),
         FontRenderOptions::Sprites {
-            file_bytes: SPRITES,
+            file_bytes: assets.sprites,
             sprites: alpha_sprites(
                 (0..10)
                     .map(|i| char_snip(3, i))
@@ -96,23 +114,57 @@ pub fn game_boy_theme<'a>(
         MetricSnips::right((39, 78), 999),
         Point::new(55, 0),
         Point::new(8, 0),
-        Color::WHITE,
-        DestroyAnimationType::Flash,
-        GameOverAnimationType::CurtainUp,
+        assets.background_color,
+        assets.destroy_animation,
+        assets.game_over_animation,
         SoundThemeOptions::default(
-            config.audio,
-            MUSIC,
-            MOVE_SOUND,
-            ROTATE_SOUND,
-            LOCK_SOUND,
-            SEND_GARBAGE_SOUND,
-            [CLEAR_SOUND, CLEAR_SOUND, CLEAR_SOUND, TETRIS_SOUND],
-            LEVEL_UP_SOUND,
-            GAME_OVER_SOUND,
-            PAUSE_SOUND,
-            VICTORY_SOUND,
+            assets.music,
+            assets.move_sound,
+            assets.rotate_sound,
+            assets.lock_sound,
+            assets.send_garbage_sound,
+            [
+                assets.clear_sound,
+                assets.clear_sound,
+                assets.clear_sound,
+                assets.tetris_sound,
+            ],
+            assets.level_up_sound,
+            assets.game_over_sound,
+            assets.pause_sound,
+            assets.victory_sound,
         )
-        .with_stack_drop(STACK_DROP_SOUND),
+        .with_stack_drop(assets.stack_drop_sound),
+    )
+}
+
+pub fn game_boy_theme<'a>(
+    canvas: &mut WindowCanvas,
+    texture_creator: &'a TextureCreator<WindowContext>,
+) -> Result<Theme<'a>, String> {
+    let options = game_boy_options(
+        ThemeName::GameBoy,
+        GameBoyAssets {
+            sprites: SPRITES,
+            background_file: BACKGROUND_FILE,
+            board_file: BOARD_FILE,
+            game_over_file: GAME_OVER_FILE,
+            music: MUSIC,
+            move_sound: MOVE_SOUND,
+            rotate_sound: ROTATE_SOUND,
+            lock_sound: LOCK_SOUND,
+            send_garbage_sound: SEND_GARBAGE_SOUND,
+            clear_sound: CLEAR_SOUND,
+            tetris_sound: TETRIS_SOUND,
+            level_up_sound: LEVEL_UP_SOUND,
+            game_over_sound: GAME_OVER_SOUND,
+            pause_sound: PAUSE_SOUND,
+            victory_sound: VICTORY_SOUND,
+            stack_drop_sound: STACK_DROP_SOUND,
+            background_color: Color::WHITE,
+            destroy_animation: DestroyAnimationType::Flash,
+            game_over_animation: GameOverAnimationType::CurtainUp,
+        },
     );
 
     retro_theme(canvas, texture_creator, options)
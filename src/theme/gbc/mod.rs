@@ -0,0 +1,59 @@
+use crate::animation::destroy::DestroyAnimationType;
+use crate::animation::game_over::GameOverAnimationType;
+use crate::theme::gb::{game_boy_options, GameBoyAssets};
+use crate::theme::retro::retro_theme;
+use crate::theme::{Theme, ThemeName};
+use sdl2::pixels::Color;
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+
+const SPRITES: &[u8] = include_bytes!("sprites.png");
+const BACKGROUND_FILE: &[u8] = include_bytes!("background.png");
+const BOARD_FILE: &[u8] = include_bytes!("board.png");
+const GAME_OVER_FILE: &[u8] = include_bytes!("game-over.png");
+
+const GAME_OVER_SOUND: &[u8] = include_bytes!("game-over.ogg");
+const LEVEL_UP_SOUND: &[u8] = include_bytes!("level-up.ogg");
+const CLEAR_SOUND: &[u8] = include_bytes!("line-clear.ogg");
+const LOCK_SOUND: &[u8] = include_bytes!("lock.ogg");
+const MOVE_SOUND: &[u8] = include_bytes!("move.ogg");
+const MUSIC: &[u8] = include_bytes!("music.ogg");
+const PAUSE_SOUND: &[u8] = include_bytes!("pause.ogg");
+const ROTATE_SOUND: &[u8] = include_bytes!("rotate.ogg");
+const SEND_GARBAGE_SOUND: &[u8] = include_bytes!("send-garbage.ogg");
+const STACK_DROP_SOUND: &[u8] = include_bytes!("stack-drop.ogg");
+const TETRIS_SOUND: &[u8] = include_bytes!("tetris.ogg");
+const VICTORY_SOUND: &[u8] = include_bytes!("victory.ogg");
+
+/// Coloured sprites & sounds on the Game Boy layout
+pub fn game_boy_color_theme<'a>(
+    canvas: &mut WindowCanvas,
+    texture_creator: &'a TextureCreator<WindowContext>,
+) -> Result<Theme<'a>, String> {
+    let options = game_boy_options(
+        ThemeName::GameBoyColor,
+        GameBoyAssets {
+            sprites: SPRITES,
+            background_file: BACKGROUND_FILE,
+            board_file: BOARD_FILE,
+            game_over_file: GAME_OVER_FILE,
+            music: MUSIC,
+            move_sound: MOVE_SOUND,
+            rotate_sound: ROTATE_SOUND,
+            lock_sound: LOCK_SOUND,
+            send_garbage_sound: SEND_GARBAGE_SOUND,
+            clear_sound: CLEAR_SOUND,
+            tetris_sound: TETRIS_SOUND,
+            level_up_sound: LEVEL_UP_SOUND,
+            game_over_sound: GAME_OVER_SOUND,
+            pause_sound: PAUSE_SOUND,
+            victory_sound: VICTORY_SOUND,
+            stack_drop_sound: STACK_DROP_SOUND,
+            background_color: Color::RGB(0xf0, 0xf8, 0xe0),
+            destroy_animation: DestroyAnimationType::Sweep,
+            game_over_animation: GameOverAnimationType::CurtainDown,
+        },
+    );
+
+    retro_theme(canvas, texture_creator, options)
+}
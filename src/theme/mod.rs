@@ -1,6 +1,7 @@
 use crate::animation::destroy::DestroyAnimationType;
 use crate::animation::game_over::{GameOverAnimate, GameOverAnimationType};
 use crate::animation::TextureAnimate;
+use crate::config::AccessibilityConfig;
 use crate::event::GameEvent;
 
 use crate::game::board::BOARD_WIDTH;
@@ -20,12 +21,15 @@ use sdl2::render::{BlendMode, Texture, TextureCreator, WindowCanvas};
 use sdl2::video::WindowContext;
 use crate::theme::helper::{CanvasRenderer, TextureFactory};
 
+pub mod accessibility;
 pub mod all;
 pub mod font;
 pub mod gb;
+pub mod gbc;
 pub mod geometry;
 pub mod modern;
 pub mod nes;
+mod pack;
 mod retro;
 pub mod snes;
 pub mod sound;
@@ -33,14 +37,19 @@ pub mod sprite_sheet;
 pub mod helper;
 
 const VISIBLE_PEEK: usize = 5;
+/// Peek queue shown by compact layouts on narrow windows
+const COMPACT_VISIBLE_PEEK: usize = 2;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
 pub enum ThemeName {
     GameBoy,
+    GameBoyColor,
     Nes,
     Snes,
     #[default]
     Modern,
+    /// Loaded from disk, indexing the theme packs
+    Pack(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -95,6 +104,8 @@ pub struct Theme<'a> {
     ghost_mino_type: MinoType,
     tetromino_scale_type: TetrominoScaleType,
     particle_color: Option<Color>,
+    /// Laid out with a narrower peek queue to fit a narrow window
+    compact: bool,
 }
 
 impl<'a> Theme<'a> {
@@ -118,7 +129,16 @@ impl<'a> Theme<'a> {
         self.board_snip
     }
 
-    pub fn draw_background(&self, canvas: &mut WindowCanvas, game: &Game) -> Result<(), String> {
+    pub fn is_compact(&self) -> bool {
+        self.compact
+    }
+
+    pub fn draw_background(
+        &self,
+        canvas: &mut WindowCanvas,
+        game: &Game,
+        compact: bool,
+    ) -> Result<(), String> {
         let metrics = game.metrics();
         canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
         canvas.clear();
@@ -135,11 +155,14 @@ impl<'a> Theme<'a> {
             self.draw_tetromino(canvas, hold_shape, self.hold_snip, false)?;
         }
 
+        let visible_peek = if compact { COMPACT_VISIBLE_PEEK } else { VISIBLE_PEEK };
         for (index, (peek_shape, peek_rect)) in metrics
             .queue
             .iter()
+            .flatten()
             .copied()
             .zip(self.peek_snips)
+            .take(visible_peek)
             .enumerate()
         {
             self.draw_tetromino(canvas, peek_shape, peek_rect, index == 0)?;
@@ -233,8 +256,12 @@ impl<'a> Theme<'a> {
         self.game_over_animation
     }
 
-    pub fn music(&self) -> &Music {
-        self.sound.music()
+    pub fn music(&self, critical: bool) -> &Music {
+        self.sound.music(critical)
+    }
+
+    pub fn has_critical_music(&self) -> bool {
+        self.sound.has_critical_music()
     }
 
     pub fn play_sound_effects(&self, event: GameEvent) -> Result<(), String> {
@@ -312,6 +339,20 @@ impl<'a> Theme<'a> {
         self.particle_color
     }
 
+    /// Only themes that already emit particles have their particle colour replaced
+    pub fn apply_accessibility(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        config: AccessibilityConfig,
+    ) -> Result<(), String> {
+        if self.particle_color.is_some() {
+            if let Some(color) = config.palette.particle_color() {
+                self.particle_color = Some(color);
+            }
+        }
+        self.sprite_sheet.apply_accessibility(canvas, config)
+    }
+
     pub fn sprite_sheet(&self) -> &TetrominoSpriteSheet<'a> {
         &self.sprite_sheet
     }
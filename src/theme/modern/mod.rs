@@ -2,6 +2,7 @@ use crate::animation::destroy::DestroyAnimationType;
 use crate::animation::game_over::GameOverAnimationType;
 use crate::config::Config;
 use crate::font::FontType;
+use crate::scale::COMPACT_RATIO;
 
 use crate::theme::font::{FontRender, MetricSnips};
 use crate::theme::geometry::{BoardGeometry, VISIBLE_BOARD_HEIGHT};
@@ -152,20 +153,70 @@ impl GameMetricsTable {
     }
 
     fn width(&self) -> u32 {
-        self.rows.iter().map(|r| r.width()).max().unwrap()
+        self.rows.iter().map(|r| r.width()).max().unwrap_or(0)
     }
 }
 
+/// Renders the modern theme as big as fits in a player's chunk of the window,
+/// laying it out compact when fitting the width would shrink it too far
 pub fn modern_theme<'a>(
     canvas: &mut WindowCanvas,
     texture_creator: &'a TextureCreator<WindowContext>,
     ttf: &Sdl2TtfContext,
     config: Config,
-    window_height: u32
+    chunk_size: (u32, u32),
+) -> Result<Theme<'a>, String> {
+    let (chunk_width, chunk_height) = chunk_size;
+    let padding_pct = config.video.screen_padding_pct();
+    let block_size = chunk_height as f64 * (1.0 - 2.0 * padding_pct) / VISIBLE_BOARD_HEIGHT as f64;
+    let padded_width = chunk_width as f64 * (1.0 - 2.0 * padding_pct);
+
+    let theme = modern_layout(canvas, texture_creator, ttf, block_size, false)?;
+    let width_scale = padded_width / theme.background_size().0 as f64;
+    if width_scale >= 1.0 {
+        return Ok(theme);
+    }
+    if width_scale >= COMPACT_RATIO {
+        let block_size = block_size * width_scale;
+        return fit_width(canvas, texture_creator, ttf, block_size, padded_width, false);
+    }
+
+    // compact layouts drop the padding, like the scaled themes
+    let theme = modern_layout(canvas, texture_creator, ttf, block_size, true)?;
+    let width_scale = chunk_width as f64 / theme.background_size().0 as f64;
+    if width_scale >= 1.0 {
+        return Ok(theme);
+    }
+    let block_size = block_size * width_scale;
+    fit_width(canvas, texture_creator, ttf, block_size, chunk_width as f64, true)
+}
+
+/// Shrinks the blocks until rounding leaves the theme no wider than the width
+fn fit_width<'a>(
+    canvas: &mut WindowCanvas,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    ttf: &Sdl2TtfContext,
+    mut block_size: f64,
+    width: f64,
+    compact: bool,
+) -> Result<Theme<'a>, String> {
+    loop {
+        let theme = modern_layout(canvas, texture_creator, ttf, block_size, compact)?;
+        if theme.background_size().0 as f64 <= width || block_size <= 1.0 {
+            return Ok(theme);
+        }
+        block_size -= 1.0;
+    }
+}
+
+/// Compact layouts stack all the metrics on the left to leave the right for a column of small peeks
+fn modern_layout<'a>(
+    canvas: &mut WindowCanvas,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    ttf: &Sdl2TtfContext,
+    block_size: f64,
+    compact: bool,
 ) -> Result<Theme<'a>, String> {
-    let block_size = (window_height as f64
-        - (2.0 * window_height as f64 * config.video.screen_padding_pct()))
-        / VISIBLE_BOARD_HEIGHT as f64;
     let border_weight = (block_size * BOARD_BORDER_PCT_OF_BLOCK).round() as u32;
     let vertical_gutter = (VERTICAL_GUTTER_PCT_OF_BLOCK * block_size).round() as u32;
     let tetromino_size = (TETROMINO_PCT_OF_BLOCK * block_size).round() as u32;
@@ -192,13 +243,17 @@ pub fn modern_theme<'a>(
         Color::WHITE,
     )?;
 
-    let metrics_left = GameMetricsTable::new(
-        &geometry,
-        &font,
-        &font_bold,
-        &[(GameMetricType::Score, MAX_SCORE)],
-    )
-    .into_right_aligned();
+    let left_metrics: &[(GameMetricType, u32)] = if compact {
+        &[
+            (GameMetricType::Score, MAX_SCORE),
+            (GameMetricType::Level, MAX_LEVEL),
+            (GameMetricType::Lines, MAX_LINES),
+        ]
+    } else {
+        &[(GameMetricType::Score, MAX_SCORE)]
+    };
+    let metrics_left =
+        GameMetricsTable::new(&geometry, &font, &font_bold, left_metrics).into_right_aligned();
     let left_gutter_width = metrics_left.width().max(tetromino_size) + vertical_gutter;
 
     let board_snip = Rect::new(
@@ -208,18 +263,20 @@ pub fn modern_theme<'a>(
         geometry.visible_height() + border_weight,
     );
 
-    let mut metrics_right = GameMetricsTable::new(
-        &geometry,
-        &font,
-        &font_bold,
+    let right_metrics: &[(GameMetricType, u32)] = if compact {
+        &[]
+    } else {
         &[
             (GameMetricType::Level, MAX_LEVEL),
             (GameMetricType::Lines, MAX_LINES),
-        ],
-    );
+        ]
+    };
+    let mut metrics_right = GameMetricsTable::new(&geometry, &font, &font_bold, right_metrics);
     metrics_right.offset_x(board_snip.right() + vertical_gutter as i32);
 
-    let right_gutter_width = metrics_right.width().max(big_tetromino_size) + vertical_gutter;
+    // compact peeks are all small, including the next tetromino
+    let peek0_size = if compact { tetromino_size } else { big_tetromino_size };
+    let right_gutter_width = metrics_right.width().max(peek0_size) + vertical_gutter;
 
     let background_width = left_gutter_width + board_snip.width() + right_gutter_width;
     let background_height = board_snip.height();
@@ -231,11 +288,11 @@ pub fn modern_theme<'a>(
         tetromino_size,
     );
 
-    let peek_offset_x = (big_tetromino_size - tetromino_size) / 2;
+    let peek_offset_x = (peek0_size - tetromino_size) / 2;
     let peek_snips = (0..VISIBLE_PEEK)
         .map(|i| {
-            let (size, offset_x, offset_y) = if i == 0 {
-                (big_tetromino_size, 0, 0)
+            let (size, offset_x, offset_y) = if i == 0 || compact {
+                (peek0_size, 0, 0)
             } else {
                 (tetromino_size, peek_offset_x, block_size)
             };
@@ -397,7 +454,6 @@ pub fn modern_theme<'a>(
             .value,
         game_over,
         sound: SoundThemeOptions::default(
-            config.audio,
             MUSIC,
             MOVE_SOUND,
             ROTATE_SOUND,
@@ -427,8 +483,13 @@ pub fn modern_theme<'a>(
         ghost_mino_type: MinoType::Perimeter,
         tetromino_scale_type: TetrominoScaleType::Fill {
             default_scale: TETROMINO_PREFERRED_BLOCK_SCALE,
-            peek0_scale: BIG_TETROMINO_PREFERRED_BLOCK_SCALE,
+            peek0_scale: if compact {
+                TETROMINO_PREFERRED_BLOCK_SCALE
+            } else {
+                BIG_TETROMINO_PREFERRED_BLOCK_SCALE
+            },
         },
         particle_color: Some(Color::WHITE),
+        compact,
     })
 }
@@ -1,6 +1,5 @@
 use crate::animation::destroy::DestroyAnimationType;
 use crate::animation::game_over::GameOverAnimationType;
-use crate::config::Config;
 use crate::theme::font::{alpha_sprites, FontRenderOptions, MetricSnips};
 use crate::theme::geometry::VISIBLE_BUFFER;
 use crate::theme::retro::{retro_theme, RetroThemeOptions};
@@ -23,12 +22,16 @@ const CLEAR_SOUND: &[u8] = include_bytes!("line-clear.ogg");
 const LOCK_SOUND: &[u8] = include_bytes!("lock.ogg");
 const MOVE_SOUND: &[u8] = include_bytes!("move.ogg");
 const MUSIC: &[u8] = include_bytes!("music.ogg");
+const CRITICAL_MUSIC: &[u8] = include_bytes!("music_crit.ogg");
 const PAUSE_SOUND: &[u8] = include_bytes!("pause.ogg");
 const ROTATE_SOUND: &[u8] = include_bytes!("rotate.ogg");
 const SEND_GARBAGE_SOUND: &[u8] = include_bytes!("send-garbage.ogg");
 const TETRIS_SOUND: &[u8] = include_bytes!("tetris.ogg");
 const VICTORY_SOUND: &[u8] = include_bytes!("victory.ogg");
 
+pub const MENU_SOUND: &[u8] = include_bytes!("menu.ogg");
+pub const MENU_START_SOUND: &[u8] = include_bytes!("menu-start.ogg");
+
 const ALPHA_PIXELS: u32 = 7;
 const BLOCK_PIXELS: u32 = 8;
 const BUFFER_PIXELS: u32 = VISIBLE_BUFFER * BLOCK_PIXELS;
@@ -44,7 +47,6 @@ fn char_snip(row: i32, col: i32) -> Point {
 pub fn nes_theme<'a>(
     canvas: &mut WindowCanvas,
     texture_creator: &'a TextureCreator<WindowContext>,
-    config: Config,
 ) -> Result<Theme<'a>, String> {
     let options = RetroThemeOptions::new(
         ThemeName::Nes,
@@ -94,7 +96,6 @@ pub fn nes_theme<'a>(
         DestroyAnimationType::Sweep,
         GameOverAnimationType::CurtainDown,
         SoundThemeOptions::default(
-            config.audio,
             MUSIC,
             MOVE_SOUND,
             ROTATE_SOUND,
@@ -105,7 +106,8 @@ pub fn nes_theme<'a>(
             GAME_OVER_SOUND,
             PAUSE_SOUND,
             VICTORY_SOUND,
-        ),
+        )
+        .with_critical_music(CRITICAL_MUSIC),
     );
     retro_theme(canvas, texture_creator, options)
 }
@@ -0,0 +1,417 @@
+use crate::animation::destroy::DestroyAnimationType;
+use crate::animation::game_over::GameOverAnimationType;
+use crate::config::config_path;
+use crate::theme::font::{alpha_sprites, FontRenderOptions, MetricSnips};
+use crate::theme::geometry::VISIBLE_BUFFER;
+use crate::theme::retro::{retro_theme, RetroThemeOptions};
+use crate::theme::sound::SoundThemeOptions;
+use crate::theme::sprite_sheet::{MinoPoints, TetrominoSpriteSheetMeta};
+use crate::theme::{Theme, ThemeName, VISIBLE_PEEK};
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{TextureCreator, WindowCanvas};
+use sdl2::video::WindowContext;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+const PACKS_DIR_NAME: &str = "themes";
+const MANIFEST_FILE_NAME: &str = "theme.yml";
+
+/// x, y in pixels
+type PointDef = (i32, i32);
+/// x, y, width, height in pixels
+type RectDef = (i32, i32, u32, u32);
+type ColorDef = (u8, u8, u8);
+
+fn point((x, y): PointDef) -> Point {
+    Point::new(x, y)
+}
+
+fn color((r, g, b): ColorDef) -> Color {
+    Color::RGB(r, g, b)
+}
+
+/// Sprite positions of a tetromino's minos, either one sprite for every mino
+/// or one per mino in the order of the tetromino's minos, optionally with different stack sprites
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum MinoDef {
+    Same(PointDef),
+    SameStacked { normal: PointDef, stack: PointDef },
+    Each([PointDef; 4]),
+    EachStacked { normal: [PointDef; 4], stack: [PointDef; 4] },
+}
+
+impl From<MinoDef> for MinoPoints {
+    fn from(value: MinoDef) -> Self {
+        match value {
+            MinoDef::Same(p) => point(p).into(),
+            MinoDef::SameStacked { normal, stack } => (point(normal), point(stack)).into(),
+            MinoDef::Each(ps) => ps.map(point).into(),
+            MinoDef::EachStacked { normal, stack } => {
+                (normal.map(point), stack.map(point)).into()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+struct MinosDef {
+    i: MinoDef,
+    j: MinoDef,
+    l: MinoDef,
+    o: MinoDef,
+    s: MinoDef,
+    t: MinoDef,
+    z: MinoDef,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MetricAlign {
+    Left,
+    Right,
+    ZeroFill,
+}
+
+/// Where a number is drawn on the background
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+struct MetricDef {
+    point: PointDef,
+    max_value: u32,
+    align: MetricAlign,
+}
+
+impl MetricDef {
+    fn snips(&self) -> MetricSnips {
+        match self.align {
+            MetricAlign::Left => MetricSnips::left(self.point, self.max_value),
+            MetricAlign::Right => MetricSnips::right(self.point, self.max_value),
+            MetricAlign::ZeroFill => MetricSnips::zero_fill(self.point, self.max_value),
+        }
+    }
+}
+
+/// Digit sprites 0 to 9
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct FontDef {
+    /// Defaults to the tetromino sprite sheet
+    #[serde(default)]
+    file: Option<String>,
+    digits: [PointDef; 10],
+    width: u32,
+    height: u32,
+    spacing: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DestroyDef {
+    Flash,
+    Sweep,
+    Particles { color: ColorDef },
+}
+
+impl From<DestroyDef> for DestroyAnimationType {
+    fn from(value: DestroyDef) -> Self {
+        match value {
+            DestroyDef::Flash => DestroyAnimationType::Flash,
+            DestroyDef::Sweep => DestroyAnimationType::Sweep,
+            DestroyDef::Particles { color: c } => {
+                DestroyAnimationType::Particles { color: color(c) }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct SoundsDef {
+    music: String,
+    move_tetromino: String,
+    rotate: String,
+    lock: String,
+    send_garbage: String,
+    /// single, double, triple, tetris
+    clear: [String; 4],
+    level_up: String,
+    game_over: String,
+    pause: String,
+    victory: String,
+    #[serde(default)]
+    critical_music: Option<String>,
+    #[serde(default)]
+    stack_drop: Option<String>,
+    #[serde(default)]
+    hard_drop: Option<String>,
+    #[serde(default)]
+    hold: Option<String>,
+}
+
+/// A theme.yml describing a retro style theme pack, file names are relative to the manifest
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct ThemeManifest {
+    sprites: String,
+    /// Size of a mino on the sprite sheet
+    block_size: u32,
+    minos: MinosDef,
+    garbage: PointDef,
+    ghost_alpha: u8,
+    background: String,
+    board: String,
+    game_over: String,
+    /// Board position on the background
+    board_point: PointDef,
+    /// Top left of the playfield on the board
+    game_point: PointDef,
+    peek: [RectDef; VISIBLE_PEEK],
+    hold: RectDef,
+    font: FontDef,
+    score: MetricDef,
+    level: MetricDef,
+    lines: MetricDef,
+    background_color: ColorDef,
+    destroy_animation: DestroyDef,
+    game_over_animation: GameOverAnimationType,
+    sounds: SoundsDef,
+}
+
+impl ThemeManifest {
+    fn parse(yaml: &str) -> Result<Self, String> {
+        serde_yaml::from_str(yaml).map_err(|e| e.to_string())
+    }
+
+    /// Background snips are positioned without the buffer that is added above the background
+    fn buffered(&self, rect: RectDef) -> Rect {
+        let buffer_pixels = (VISIBLE_BUFFER * self.block_size) as i32;
+        let (x, y, width, height) = rect;
+        Rect::new(x, y + buffer_pixels, width, height)
+    }
+
+    fn options(&self, dir: &Path, name: ThemeName) -> Result<RetroThemeOptions, String> {
+        let sprites = read_asset(dir, &self.sprites)?;
+        let sounds = &self.sounds;
+        let [single, double, triple, tetris] = &sounds.clear;
+        let mut sound = SoundThemeOptions::default(
+            read_asset(dir, &sounds.music)?,
+            read_asset(dir, &sounds.move_tetromino)?,
+            read_asset(dir, &sounds.rotate)?,
+            read_asset(dir, &sounds.lock)?,
+            read_asset(dir, &sounds.send_garbage)?,
+            [
+                read_asset(dir, single)?,
+                read_asset(dir, double)?,
+                read_asset(dir, triple)?,
+                read_asset(dir, tetris)?,
+            ],
+            read_asset(dir, &sounds.level_up)?,
+            read_asset(dir, &sounds.game_over)?,
+            read_asset(dir, &sounds.pause)?,
+            read_asset(dir, &sounds.victory)?,
+        );
+        if let Some(file) = sounds.critical_music.as_ref() {
+            sound = sound.with_critical_music(read_asset(dir, file)?);
+        }
+        if let Some(file) = sounds.stack_drop.as_ref() {
+            sound = sound.with_stack_drop(read_asset(dir, file)?);
+        }
+        if let Some(file) = sounds.hard_drop.as_ref() {
+            sound = sound.with_hard_drop(read_asset(dir, file)?);
+        }
+        if let Some(file) = sounds.hold.as_ref() {
+            sound = sound.with_hold(read_asset(dir, file)?);
+        }
+
+        let font_file = match self.font.file.as_ref() {
+            Some(file) => read_asset(dir, file)?,
+            None => sprites,
+        };
+
+        Ok(RetroThemeOptions::new(
+            name,
+            TetrominoSpriteSheetMeta::new(
+                sprites,
+                self.block_size,
+                self.minos.i,
+                self.minos.j,
+                self.minos.l,
+                self.minos.o,
+                self.minos.s,
+                self.minos.t,
+                self.minos.z,
+                point(self.garbage),
+                self.ghost_alpha,
+            ),
+            read_asset(dir, &self.background)?,
+            read_asset(dir, &self.board)?,
+            read_asset(dir, &self.game_over)?,
+            self.peek.map(|r| self.buffered(r)),
+            self.buffered(self.hold),
+            FontRenderOptions::Sprites {
+                file_bytes: font_file,
+                sprites: alpha_sprites(
+                    self.font.digits.map(point),
+                    self.font.width,
+                    self.font.height,
+                ),
+                spacing: self.font.spacing,
+            },
+            self.score.snips(),
+            self.level.snips(),
+            self.lines.snips(),
+            point(self.board_point),
+            point(self.game_point),
+            color(self.background_color),
+            self.destroy_animation.into(),
+            self.game_over_animation,
+            sound,
+        ))
+    }
+}
+
+/// Pack assets are read once at startup and kept for the life of the app like the built in themes,
+/// music in particular has to be static
+fn read_asset(dir: &Path, file: &str) -> Result<&'static [u8], String> {
+    let path = dir.join(file);
+    let bytes = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(Box::leak(bytes.into_boxed_slice()))
+}
+
+/// Each theme pack is a folder in the themes folder next to the config, loaded in name order.
+/// An unreadable themes folder is skipped like a bad pack
+fn pack_dirs() -> Result<Vec<PathBuf>, String> {
+    let packs_dir = config_path(PACKS_DIR_NAME)?.with_extension("");
+    if !packs_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let entries = match std::fs::read_dir(&packs_dir) {
+        Ok(entries) => entries,
+        Err(error) => {
+            println!("Bad theme packs folder at {}, {}, skipping", packs_dir.display(), error);
+            return Ok(vec![]);
+        }
+    };
+    let mut result = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.join(MANIFEST_FILE_NAME).is_file())
+        .collect::<Vec<PathBuf>>();
+    result.sort();
+    Ok(result)
+}
+
+fn pack_name(dir: &Path) -> String {
+    dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn load_pack<'a>(
+    canvas: &mut WindowCanvas,
+    texture_creator: &'a TextureCreator<WindowContext>,
+    dir: &Path,
+    name: ThemeName,
+) -> Result<Theme<'a>, String> {
+    let yaml = std::fs::read_to_string(dir.join(MANIFEST_FILE_NAME)).map_err(|e| e.to_string())?;
+    let options = ThemeManifest::parse(&yaml)?.options(dir, name)?;
+    retro_theme(canvas, texture_creator, options)
+}
+
+/// Bad packs are skipped so that one broken skin does not stop the game from starting,
+/// each pack is returned with its folder name
+pub fn theme_packs<'a>(
+    canvas: &mut WindowCanvas,
+    texture_creator: &'a TextureCreator<WindowContext>,
+) -> Result<Vec<(String, Theme<'a>)>, String> {
+    let mut result = vec![];
+    for dir in pack_dirs()? {
+        #[cfg(debug_assertions)]
+        println!("loading theme pack: {}", dir.display());
+        match load_pack(canvas, texture_creator, &dir, ThemeName::Pack(result.len())) {
+            Ok(theme) => result.push((pack_name(&dir), theme)),
+            Err(error) => println!("Bad theme pack at {}, {}, skipping", dir.display(), error),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+sprites: sprites.png
+block_size: 8
+minos:
+  i: [[1, 35], [9, 35], [17, 35], [25, 35]]
+  j: [51, 26]
+  l: { normal: [26, 26], stack: [26, 34] }
+  o: [1, 1]
+  s: [51, 1]
+  t: [1, 26]
+  z: [18, 1]
+garbage: [34, 35]
+ghost_alpha: 48
+background: background.png
+board: board.png
+game_over: game-over.png
+board_point: [55, 0]
+game_point: [8, 0]
+peek:
+  - [162, 11, 32, 32]
+  - [162, 48, 32, 32]
+  - [162, 72, 32, 32]
+  - [162, 96, 32, 32]
+  - [162, 120, 32, 32]
+hold: [12, 101, 32, 32]
+font:
+  digits: [[1, 66], [9, 66], [17, 66], [25, 66], [33, 66],
+           [41, 66], [49, 66], [57, 66], [65, 66], [73, 66]]
+  width: 6
+  height: 6
+  spacing: 2
+score: { point: [46, 25], max_value: 999999, align: right }
+level: { point: [39, 52], max_value: 999, align: zero_fill }
+lines: { point: [39, 78], max_value: 999, align: left }
+background_color: [255, 255, 255]
+destroy_animation: flash
+game_over_animation: curtain_up
+sounds:
+  music: music.ogg
+  move_tetromino: move.ogg
+  rotate: rotate.ogg
+  lock: lock.ogg
+  send_garbage: send-garbage.ogg
+  clear: [clear.ogg, clear.ogg, clear.ogg, tetris.ogg]
+  level_up: level-up.ogg
+  game_over: game-over.ogg
+  pause: pause.ogg
+  victory: victory.ogg
+  stack_drop: stack-drop.ogg
+"#;
+
+    #[test]
+    fn parses_manifest() {
+        let manifest = ThemeManifest::parse(EXAMPLE).unwrap();
+        assert_eq!(manifest.minos.i, MinoDef::Each([(1, 35), (9, 35), (17, 35), (25, 35)]));
+        assert_eq!(manifest.minos.j, MinoDef::Same((51, 26)));
+        assert_eq!(
+            manifest.minos.l,
+            MinoDef::SameStacked { normal: (26, 26), stack: (26, 34) }
+        );
+        assert_eq!(manifest.font.file, None);
+        assert_eq!(manifest.score.align, MetricAlign::Right);
+        assert_eq!(manifest.destroy_animation, DestroyDef::Flash);
+        assert_eq!(manifest.game_over_animation, GameOverAnimationType::CurtainUp);
+        assert_eq!(manifest.sounds.stack_drop, Some("stack-drop.ogg".to_string()));
+        assert_eq!(manifest.sounds.hold, None);
+    }
+
+    #[test]
+    fn background_snips_are_buffered() {
+        let manifest = ThemeManifest::parse(EXAMPLE).unwrap();
+        let buffer_pixels = (VISIBLE_BUFFER * 8) as i32;
+        assert_eq!(manifest.buffered(manifest.hold), Rect::new(12, 101 + buffer_pixels, 32, 32));
+    }
+
+    #[test]
+    fn bad_manifest() {
+        assert!(ThemeManifest::parse("sprites: sprites.png").is_err());
+    }
+}
@@ -164,5 +164,6 @@ pub fn retro_theme<'a>(
         ghost_mino_type: MinoType::Ghost,
         tetromino_scale_type: TetrominoScaleType::Center,
         particle_color: None,
+        compact: false,
     })
 }
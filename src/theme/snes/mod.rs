@@ -1,6 +1,5 @@
 use crate::animation::destroy::DestroyAnimationType;
 use crate::animation::game_over::GameOverAnimationType;
-use crate::config::Config;
 use crate::theme::font::{alpha_sprites, FontRenderOptions, MetricSnips};
 use crate::theme::geometry::VISIBLE_BUFFER;
 use crate::theme::retro::{retro_theme, RetroThemeOptions};
@@ -23,6 +22,7 @@ const CLEAR_SOUND: &[u8] = include_bytes!("line-clear.ogg");
 const LOCK_SOUND: &[u8] = include_bytes!("lock.ogg");
 const MOVE_SOUND: &[u8] = include_bytes!("move.ogg");
 const MUSIC: &[u8] = include_bytes!("music.ogg");
+const CRITICAL_MUSIC: &[u8] = include_bytes!("music-critical.ogg");
 const PAUSE_SOUND: &[u8] = include_bytes!("pause.ogg");
 const ROTATE_SOUND: &[u8] = include_bytes!("rotate.ogg");
 const SEND_GARBAGE_SOUND: &[u8] = include_bytes!("send-garbage.ogg");
@@ -30,6 +30,8 @@ const STACK_DROP_SOUND: &[u8] = include_bytes!("stack-drop.ogg");
 const TETRIS_SOUND: &[u8] = include_bytes!("tetris.ogg");
 const VICTORY_SOUND: &[u8] = include_bytes!("victory.ogg");
 
+pub const MENU_MUSIC: &[u8] = include_bytes!("music-menu.ogg");
+
 const ALPHA_WIDTH: u32 = 7;
 const ALPHA_HEIGHT: u32 = 8;
 const BLOCK_PIXELS: u32 = 8;
@@ -46,7 +48,6 @@ fn char_snip(row: i32, col: i32) -> Point {
 pub fn snes_theme<'a>(
     canvas: &mut WindowCanvas,
     texture_creator: &'a TextureCreator<WindowContext>,
-    config: Config,
 ) -> Result<Theme<'a>, String> {
     let options = RetroThemeOptions::new(
         ThemeName::Snes,
@@ -96,7 +97,6 @@ pub fn snes_theme<'a>(
         DestroyAnimationType::Sweep,
         GameOverAnimationType::CurtainDown,
         SoundThemeOptions::default(
-            config.audio,
             MUSIC,
             MOVE_SOUND,
             ROTATE_SOUND,
@@ -108,6 +108,7 @@ pub fn snes_theme<'a>(
             PAUSE_SOUND,
             VICTORY_SOUND,
         )
+        .with_critical_music(CRITICAL_MUSIC)
         .with_stack_drop(STACK_DROP_SOUND),
     );
     retro_theme(canvas, texture_creator, options)
@@ -1,16 +1,16 @@
-use crate::config::AudioConfig;
 use crate::event::GameEvent;
+use crate::game::board::BOARD_HEIGHT;
 
 use rand::{thread_rng, Rng};
 use sdl2::get_error;
 use sdl2::mixer::{Chunk, Music};
 use sdl2::rwops::RWops;
 use sdl2::sys::mixer;
+use std::time::Duration;
 
-pub fn load_sound(buffer: &[u8], config: AudioConfig) -> Result<Chunk, String> {
-    let mut chunk = chunk_from_buffer(buffer)?;
-    chunk.set_volume(config.effects_volume());
-    Ok(chunk)
+/// Sound effects are loaded at full volume, the effects volume is applied to the mixer channels
+pub fn load_sound(buffer: &[u8]) -> Result<Chunk, String> {
+    chunk_from_buffer(buffer)
 }
 
 pub fn play_sound(chunk: &Chunk) -> Result<(), String> {
@@ -19,6 +19,75 @@ pub fn play_sound(chunk: &Chunk) -> Result<(), String> {
     Ok(())
 }
 
+/// Stack height that switches to the critical music, the stack is in the top 5 rows
+const CRITICAL_STACK_HEIGHT: u32 = BOARD_HEIGHT - 5;
+/// Stack height to drop to before switching back to the normal music, below the top 8 rows
+const SAFE_STACK_HEIGHT: u32 = BOARD_HEIGHT - 8;
+
+/// Tracks when to play the critical music, with hysteresis so that it does not flap
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CriticalMusic {
+    critical: bool,
+}
+
+impl CriticalMusic {
+    pub fn is_critical(&self) -> bool {
+        self.critical
+    }
+
+    /// Returns true when the music should switch
+    pub fn update(&mut self, stack_height: u32) -> bool {
+        let critical = if self.critical {
+            stack_height > SAFE_STACK_HEIGHT
+        } else {
+            stack_height > CRITICAL_STACK_HEIGHT
+        };
+        let changed = critical != self.critical;
+        self.critical = critical;
+        changed
+    }
+}
+
+/// Time taken to fade out the playing music before the next track fades in
+const MUSIC_FADE_OUT: Duration = Duration::from_millis(500);
+
+/// Crossfades between tracks, fading out the playing music before the next track fades in
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MusicFade {
+    fade_out: Option<Duration>,
+}
+
+impl MusicFade {
+    /// Starts fading out the playing music, a change during a fade out just waits for it
+    pub fn start(&mut self) -> Result<(), String> {
+        if self.fade_out.is_none() {
+            Music::fade_out(MUSIC_FADE_OUT.as_millis() as i32)?;
+            self.fade_out = Some(Duration::ZERO);
+        }
+        Ok(())
+    }
+
+    /// The music was switched without a fade, e.g. while paused
+    pub fn cancel(&mut self) {
+        self.fade_out = None;
+    }
+
+    /// Returns true once the music has faded out and the next track should be faded in
+    pub fn update(&mut self, delta: Duration) -> bool {
+        match self.fade_out {
+            Some(duration) if duration + delta >= MUSIC_FADE_OUT => {
+                self.fade_out = None;
+                true
+            }
+            Some(duration) => {
+                self.fade_out = Some(duration + delta);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
 fn chunk_from_buffer(buffer: &[u8]) -> Result<Chunk, String> {
     let raw = unsafe { mixer::Mix_LoadWAV_RW(RWops::from_bytes(buffer)?.raw(), 0) };
     if raw.is_null() {
@@ -30,8 +99,8 @@ fn chunk_from_buffer(buffer: &[u8]) -> Result<Chunk, String> {
 
 #[derive(Debug, Clone)]
 pub struct SoundThemeOptions {
-    config: AudioConfig,
     music: &'static [u8],
+    critical_music: Option<&'static [u8]>,
     move_tetromino: &'static [u8],
     rotate: &'static [u8],
     lock: &'static [u8],
@@ -48,7 +117,6 @@ pub struct SoundThemeOptions {
 
 impl SoundThemeOptions {
     pub fn default(
-        config: AudioConfig,
         music: &'static [u8],
         move_tetromino: &'static [u8],
         rotate: &'static [u8],
@@ -61,8 +129,8 @@ impl SoundThemeOptions {
         victory: &'static [u8],
     ) -> Self {
         Self {
-            config,
             music,
+            critical_music: None,
             move_tetromino,
             rotate,
             lock,
@@ -78,6 +146,12 @@ impl SoundThemeOptions {
         }
     }
 
+    /// Played instead of the music while the stack is high
+    pub fn with_critical_music(mut self, value: &'static [u8]) -> Self {
+        self.critical_music = Some(value);
+        self
+    }
+
     pub fn with_stack_drop(mut self, value: &'static [u8]) -> Self {
         self.stack_drop = Some(value);
         self
@@ -102,8 +176,12 @@ impl SoundThemeOptions {
         Music::from_static_bytes(self.music)
     }
 
+    fn load_critical_music<'a>(&self) -> Result<Option<Music<'a>>, String> {
+        self.critical_music.map(Music::from_static_bytes).transpose()
+    }
+
     fn load_sound(&self, buffer: &[u8]) -> Result<Chunk, String> {
-        load_sound(buffer, self.config)
+        load_sound(buffer)
     }
 
     pub fn build(self) -> Result<SoundTheme, String> {
@@ -113,6 +191,7 @@ impl SoundThemeOptions {
 
 pub struct SoundTheme {
     music: Music<'static>,
+    critical_music: Option<Music<'static>>,
     move_tetromino: Chunk,
     rotate: Chunk,
     lock: Chunk,
@@ -132,6 +211,7 @@ impl SoundTheme {
         let o = options.clone();
         Ok(Self {
             music: options.load_music()?,
+            critical_music: options.load_critical_music()?,
             move_tetromino: options.load_sound(o.move_tetromino)?,
             rotate: options.load_sound(o.rotate)?,
             lock: options.load_sound(o.lock)?,
@@ -151,8 +231,16 @@ impl SoundTheme {
         })
     }
 
-    pub fn music(&self) -> &Music {
-        &self.music
+    /// The critical music if requested and available, otherwise the normal music
+    pub fn music(&self, critical: bool) -> &Music {
+        match &self.critical_music {
+            Some(critical_music) if critical => critical_music,
+            _ => &self.music,
+        }
+    }
+
+    pub fn has_critical_music(&self) -> bool {
+        self.critical_music.is_some()
     }
 
     pub fn receive_event(&self, event: GameEvent) -> Result<(), String> {
@@ -204,3 +292,35 @@ impl SoundTheme {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn critical_music_has_hysteresis() {
+        let mut critical_music = CriticalMusic::default();
+        assert!(!critical_music.update(CRITICAL_STACK_HEIGHT));
+        assert!(critical_music.update(CRITICAL_STACK_HEIGHT + 1));
+        assert!(critical_music.is_critical());
+        assert!(!critical_music.update(SAFE_STACK_HEIGHT + 1));
+        assert!(critical_music.is_critical());
+        assert!(critical_music.update(SAFE_STACK_HEIGHT));
+        assert!(!critical_music.is_critical());
+    }
+
+    #[test]
+    fn music_fades_in_after_fading_out() {
+        let mut music_fade = MusicFade::default();
+        assert!(!music_fade.update(MUSIC_FADE_OUT));
+
+        music_fade.fade_out = Some(Duration::ZERO);
+        assert!(!music_fade.update(MUSIC_FADE_OUT / 2));
+        assert!(music_fade.update(MUSIC_FADE_OUT / 2));
+        assert!(!music_fade.update(MUSIC_FADE_OUT));
+
+        music_fade.fade_out = Some(Duration::ZERO);
+        music_fade.cancel();
+        assert!(!music_fade.update(MUSIC_FADE_OUT));
+    }
+}
@@ -1,7 +1,9 @@
+use crate::config::{AccessibilityConfig, MinoPalette};
 use crate::game::block::BlockState;
 use crate::game::geometry::Rotation;
 use crate::game::tetromino::{Corner, Perimeter, TetrominoShape};
 use crate::game::Game;
+use crate::theme::accessibility::draw_glyph;
 use crate::theme::geometry::{BoardGeometry, VISIBLE_BOARD_HEIGHT};
 #[allow(unused_imports)]
 use bitflags::Flags;
@@ -33,6 +35,15 @@ impl<'a> TetrominoTexture<'a> {
             MinoType::Perimeter => &self.perimeter,
         }
     }
+
+    fn texture_mut(&mut self, mino_type: MinoType) -> &mut Texture<'a> {
+        match mino_type {
+            MinoType::Normal => &mut self.normal,
+            MinoType::Ghost => &mut self.ghost,
+            MinoType::Stack => &mut self.stack,
+            MinoType::Perimeter => &mut self.perimeter,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -232,6 +243,33 @@ fn draw_sprites<'a>(
     Ok(texture)
 }
 
+fn recolor_minos(
+    canvas: &mut WindowCanvas,
+    shape: TetrominoShape,
+    snips: [Rect; 4],
+    mino_type: MinoType,
+    config: AccessibilityConfig,
+) -> Result<(), String> {
+    if let Some(color) = config.palette.mino_color(shape) {
+        // lighten towards white then multiply by the palette colour, both keep the alpha
+        let lighten = 0xff - config.palette.shading();
+        canvas.set_blend_mode(BlendMode::Add);
+        canvas.set_draw_color(Color::RGBA(lighten, lighten, lighten, 0xff));
+        canvas.fill_rect(None)?;
+        canvas.set_blend_mode(BlendMode::Mod);
+        canvas.set_draw_color(color);
+        canvas.fill_rect(None)?;
+    }
+    if config.glyphs && mino_type != MinoType::Perimeter {
+        canvas.set_blend_mode(BlendMode::Blend);
+        for snip in snips {
+            draw_glyph(canvas, shape, snip)?;
+        }
+    }
+    canvas.set_blend_mode(BlendMode::None);
+    Ok(())
+}
+
 fn draw_perimeter<'a>(
     canvas: &mut WindowCanvas,
     texture_creator: &'a TextureCreator<WindowContext>,
@@ -389,6 +427,37 @@ impl<'a> TetrominoSpriteSheet<'a> {
         })
     }
 
+    /// Recolour and mark the minos so that shapes can be told apart without the theme colours
+    pub fn apply_accessibility(
+        &mut self,
+        canvas: &mut WindowCanvas,
+        config: AccessibilityConfig,
+    ) -> Result<(), String> {
+        if config.palette == MinoPalette::Theme && !config.glyphs {
+            return Ok(());
+        }
+        for (shape, tetromino) in self.tetrominos.iter_mut() {
+            let shape = *shape;
+            let snips = tetromino.snips;
+            let mino_types = [
+                MinoType::Normal,
+                MinoType::Ghost,
+                MinoType::Stack,
+                MinoType::Perimeter,
+            ];
+            for mino_type in mino_types {
+                let mut result = Ok(());
+                canvas
+                    .with_texture_canvas(tetromino.texture_mut(mino_type), |c| {
+                        result = recolor_minos(c, shape, snips, mino_type, config);
+                    })
+                    .map_err(|e| e.to_string())?;
+                result?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn draw_garbage(&self, canvas: &mut WindowCanvas, dest: Point) -> Result<(), String> {
         canvas.copy(&self.garbage, None, self.mino_rect(dest))
     }
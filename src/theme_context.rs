@@ -31,6 +31,39 @@ impl<'a> PlayerTextures<'a> {
 
         Ok(Self { background, board })
     }
+
+    /// Textures for each player, big enough for any of the themes
+    pub fn for_players(
+        texture_creator: &'a TextureCreator<WindowContext>,
+        themes: &ThemeContext,
+        players: u32,
+    ) -> Result<Vec<Self>, String> {
+        (0..players)
+            .map(|_| {
+                Self::new(
+                    texture_creator,
+                    themes.max_background_size(),
+                    themes.max_board_size(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Mut refs of all textures and their render modes in a single vector so we can render to texture in one loop
+pub fn player_texture_refs<'a, 'b>(
+    player_textures: &'b mut [PlayerTextures<'a>],
+) -> Vec<(&'b mut Texture<'a>, TextureMode)> {
+    let mut texture_refs = vec![];
+    for (player_index, textures) in player_textures.iter_mut().enumerate() {
+        let player = player_index as u32 + 1;
+        texture_refs.push((
+            &mut textures.background,
+            TextureMode::PlayerBackground(player),
+        ));
+        texture_refs.push((&mut textures.board, TextureMode::PlayerBoard(player)));
+    }
+    texture_refs
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -114,6 +147,7 @@ pub struct ThemeContext<'a> {
     themes: Vec<ScaledTheme<'a>>,
     fade_buffer: Texture<'a>,
     fade_duration: Option<Duration>,
+    reduced_motion: bool,
 }
 
 impl<'a> ThemeContext<'a> {
@@ -130,23 +164,42 @@ impl<'a> ThemeContext<'a> {
 
         let current = match game_config.themes {
             MatchThemes::All | MatchThemes::GameBoy => 0,
-            MatchThemes::Nes => 1,
-            MatchThemes::Snes => 2,
-            MatchThemes::Modern => 3,
+            MatchThemes::GameBoyColor => 1,
+            MatchThemes::Nes => 2,
+            MatchThemes::Snes => 3,
+            MatchThemes::Modern => 4,
+            // packs follow the built in themes
+            MatchThemes::Pack(index) => MatchThemes::count() + index,
         };
+        let themes = all_themes
+            .all()
+            .iter()
+            .map(|theme| ScaledTheme::new(theme, game_config.players, window_size, config.video))
+            .collect::<Vec<ScaledTheme>>();
+        // the pack of a saved match may have been removed since
+        let current = if current < themes.len() { current } else { 0 };
 
         Ok(Self {
             current,
-            themes: all_themes
-                .all()
-                .iter()
-                .map(|theme| ScaledTheme::new(theme, game_config.players, window_size, config.video))
-                .collect(),
+            themes,
             fade_buffer,
             fade_duration: None,
+            reduced_motion: config.accessibility.reduced_motion,
         })
     }
 
+    /// Carries on from a theme after everything is rebuilt, e.g. for a new window size
+    pub fn with_current(mut self, current: usize) -> Self {
+        if current < self.themes.len() {
+            self.current = current;
+        }
+        self
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
     pub fn max_background_size(&self) -> (u32, u32) {
         let sizes = self
             .themes
@@ -206,6 +259,10 @@ impl<'a> ThemeContext<'a> {
     }
 
     pub fn start_fade(&mut self, canvas: &mut WindowCanvas) -> Result<(), String> {
+        if self.reduced_motion {
+            // cut straight to the next theme
+            return Ok(());
+        }
         self.fade_duration = Some(Duration::ZERO);
 
         let query = self.fade_buffer.query();
@@ -223,6 +280,12 @@ impl<'a> ThemeContext<'a> {
         self.fade_duration.is_some()
     }
 
+    /// The current theme is laid out for a narrow window
+    pub fn is_compact(&self) -> bool {
+        let current = self.current();
+        current.scale.is_compact() || current.theme.is_compact()
+    }
+
     pub fn render_bg_particles(&self) -> bool {
         self.current().theme.particle_color().is_some()
     }
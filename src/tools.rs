@@ -0,0 +1,110 @@
+use crate::game::block::BlockState;
+use crate::game::board::{compact_destroy_lines, BOARD_WIDTH};
+use crate::game::fumen::{self, shape_char, FumenPage};
+use crate::game::geometry::Point;
+use crate::game::rotation::RotationSystem;
+use crate::game::tetromino::TetrominoShape;
+
+const USAGE: &str = "usage: rustris fumen <v115 fumen>";
+
+/// Runs a headless tool from the command line arguments instead of the game,
+/// None when the arguments do not ask for a tool
+pub fn run(args: &[String]) -> Option<Result<String, String>> {
+    match args {
+        [command, fumen] if command == "fumen" => Some(describe_fumen(fumen)),
+        [command, ..] if command == "fumen" => Some(Err(USAGE.to_string())),
+        _ => None,
+    }
+}
+
+/// The stack in the puzzle format with the tetromino in play in lower case,
+/// then the hold, queue & the fumen left once the tetromino locks and clears its lines
+fn describe_fumen(fumen: &str) -> Result<String, String> {
+    let page = fumen::decode(fumen)?;
+    let mut board = page.to_board(RotationSystem::default())?;
+    let piece_minos = board.tetromino().map_or(vec![], |t| t.minos().to_vec());
+
+    let top = (0..fumen::FUMEN_HEIGHT)
+        .rev()
+        .find(|y| {
+            board.row(*y).iter().any(|b| b.collides())
+                || piece_minos.iter().any(|p| p.y == *y as i32)
+        })
+        .map_or(0, |y| y + 1);
+    let mut lines = (0..top)
+        .rev()
+        .map(|y| {
+            (0..BOARD_WIDTH as i32)
+                .map(|x| match board.block(Point::new(x, y as i32)) {
+                    BlockState::Tetromino(shape, _, _) => shape_char(shape).to_ascii_lowercase(),
+                    BlockState::Stack(shape, _, _) => shape_char(shape),
+                    BlockState::Garbage => '#',
+                    _ => '.',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>();
+
+    let shape_chars =
+        |shapes: &[TetrominoShape]| shapes.iter().map(|s| shape_char(*s)).collect::<String>();
+    lines.push(format!("hold: {}", shape_chars(page.hold.as_slice())));
+    lines.push(format!("current: {}", shape_chars(page.current.as_slice())));
+    lines.push(format!("queue: {}", shape_chars(&page.queue)));
+
+    board.lock();
+    let pattern = board.pattern();
+    let cleared = compact_destroy_lines(pattern)
+        .iter()
+        .map(|y| y.to_string())
+        .collect::<Vec<String>>();
+    lines.push(format!("cleared lines: {}", cleared.join(" ")));
+    board.destroy(pattern);
+
+    let mut next = FumenPage::from_board(&board);
+    next.hold = page.hold;
+    next.current = page.queue.first().copied();
+    next.queue = page.queue.iter().skip(1).copied().collect();
+    lines.push(format!("next: {}", fumen::encode(&next)));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn only_runs_when_asked() {
+        assert!(run(&[]).is_none());
+        assert!(run(&args(&["--something-else"])).is_none());
+        assert_eq!(run(&args(&["fumen"])), Some(Err(USAGE.to_string())));
+        assert!(run(&args(&["fumen", "nonsense"])).unwrap().is_err());
+    }
+
+    #[test]
+    fn describes_a_fumen() {
+        let fumen = "v115@HhA8AehlFeB8CeH8AeF8JelKYXAFLDmClcJSAVjiSA?VG88AYe88A5sDCA";
+        let output = run(&args(&["fumen", fumen])).unwrap().unwrap();
+        let lines = output.lines().collect::<Vec<&str>>();
+        assert_eq!(
+            lines[..7],
+            [
+                "#.LL......",
+                "##ttt#####",
+                "###t######",
+                "hold: O",
+                "current: T",
+                "queue: SZ",
+                "cleared lines: 0 1",
+            ]
+        );
+
+        // carries on with the stack left after the clear and the next tetromino
+        let next = fumen::decode(lines[7].strip_prefix("next: ").unwrap()).unwrap();
+        assert_eq!(next.field[0], fumen::decode(fumen).unwrap().field[2]);
+        assert_eq!(next.current, Some(TetrominoShape::S));
+    }
+}